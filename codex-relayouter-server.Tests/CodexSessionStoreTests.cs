@@ -62,6 +62,81 @@ public sealed class CodexSessionStoreTests
         }
     }
 
+    [Fact]
+    public void ListRecent_OffsetSkipsNewestSessions()
+    {
+        var sessionIds = Enumerable.Range(0, 3).Select(_ => Guid.NewGuid().ToString()).ToArray();
+        var sessionsRoot = CreateTempSessionsRoot();
+        var dir = Path.Combine(sessionsRoot, "tests", "listrecent-offset", Guid.NewGuid().ToString("N"));
+        Directory.CreateDirectory(dir);
+
+        try
+        {
+            var future = DateTime.UtcNow.AddYears(50);
+            for (var i = 0; i < sessionIds.Length; i++)
+            {
+                var path = Path.Combine(dir, $"rollout-test-{sessionIds[i]}.jsonl");
+                File.WriteAllLines(
+                    path,
+                    new[]
+                    {
+                        BuildSessionMetaLine(sessionIds[i]),
+                        BuildUserMessageLine($"hello {i}"),
+                    },
+                    new UTF8Encoding(encoderShouldEmitUTF8Identifier: false));
+                File.SetLastWriteTimeUtc(path, future.AddMinutes(-i));
+            }
+
+            var store = CreateStore();
+            var firstPage = store.ListRecent(limit: 2);
+            var secondPage = store.ListRecent(limit: 2, offset: 2);
+
+            Assert.Equal(new[] { sessionIds[0], sessionIds[1] }, firstPage.Select(s => s.Id).ToArray());
+            Assert.Equal(new[] { sessionIds[2] }, secondPage.Select(s => s.Id).ToArray());
+        }
+        finally
+        {
+            ClearTempSessionsRoot(sessionsRoot);
+        }
+    }
+
+    [Fact]
+    public void ListRecent_ReportsFileWriteTimeAsUpdatedAt()
+    {
+        var sessionId = Guid.NewGuid().ToString();
+        var sessionsRoot = CreateTempSessionsRoot();
+        var dir = Path.Combine(sessionsRoot, "tests", "listrecent-updated", Guid.NewGuid().ToString("N"));
+        Directory.CreateDirectory(dir);
+        var filePath = Path.Combine(dir, $"rollout-test-{sessionId}.jsonl");
+
+        try
+        {
+            File.WriteAllLines(
+                filePath,
+                new[]
+                {
+                    BuildSessionMetaLine(sessionId),
+                    BuildUserMessageLine("hello"),
+                },
+                new UTF8Encoding(encoderShouldEmitUTF8Identifier: false));
+
+            var lastWrite = new DateTime(2030, 1, 2, 3, 4, 5, DateTimeKind.Utc);
+            File.SetLastWriteTimeUtc(filePath, lastWrite);
+
+            var store = CreateStore();
+            var summary = Assert.Single(store.ListRecent(limit: 10));
+
+            Assert.Equal(sessionId, summary.Id);
+            Assert.Equal(new DateTimeOffset(lastWrite), summary.UpdatedAt);
+            Assert.True(summary.CreatedAt < summary.UpdatedAt);
+        }
+        finally
+        {
+            TryDeleteFile(filePath);
+            ClearTempSessionsRoot(sessionsRoot);
+        }
+    }
+
     [Fact]
     public void ReadMessages_FlushesTrailingTraceAsAssistantMessage()
     {
@@ -106,6 +181,49 @@ public sealed class CodexSessionStoreTests
         }
     }
 
+    [Fact]
+    public void ReadMessages_KeepsApplyPatchInputInPatchField()
+    {
+        var sessionId = Guid.NewGuid().ToString();
+        var sessionsRoot = CreateTempSessionsRoot();
+        var dir = Path.Combine(sessionsRoot, "tests", DateTimeOffset.UtcNow.ToString("yyyyMMdd"));
+        Directory.CreateDirectory(dir);
+        var filePath = Path.Combine(dir, $"rollout-test-{sessionId}.jsonl");
+
+        const string patch = "*** Begin Patch\n*** Add File: hello.txt\n+hi\n*** End Patch\n";
+
+        try
+        {
+            File.WriteAllLines(
+                filePath,
+                new[]
+                {
+                    BuildSessionMetaLine(sessionId),
+                    BuildUserMessageLine("add a file"),
+                    BuildFunctionCallLine(callId: "call_1", tool: "apply_patch", argsJson: JsonString(new { input = patch })),
+                    BuildFunctionCallOutputLine(callId: "call_1", output: "Success. Updated the following files:\nA hello.txt\n"),
+                    BuildAssistantMessageLine("done"),
+                },
+                new UTF8Encoding(encoderShouldEmitUTF8Identifier: false));
+
+            var store = CreateStore();
+            var messages = store.ReadMessages(sessionId, limit: 200);
+
+            Assert.NotNull(messages);
+            var assistant = Assert.Single(messages!, m => string.Equals(m.Role, "assistant", StringComparison.OrdinalIgnoreCase));
+            var entry = Assert.Single(assistant.Trace!);
+            Assert.Equal("apply_patch", entry.Tool);
+            Assert.Equal(patch, entry.Patch);
+            Assert.Null(entry.Text);
+            Assert.Contains("A hello.txt", entry.Output);
+        }
+        finally
+        {
+            TryDeleteFile(filePath);
+            ClearTempSessionsRoot(sessionsRoot);
+        }
+    }
+
     [Fact]
     public void ReadMessages_UsesAgentMessageWhenAssistantMessageMissing()
     {
@@ -189,6 +307,46 @@ public sealed class CodexSessionStoreTests
         }
     }
 
+    [Fact]
+    public void ReadMessages_BeforePagesBackByStableOrdinal()
+    {
+        var sessionId = Guid.NewGuid().ToString();
+        var sessionsRoot = CreateTempSessionsRoot();
+        var dir = Path.Combine(sessionsRoot, "tests", DateTimeOffset.UtcNow.ToString("yyyyMMdd"));
+        Directory.CreateDirectory(dir);
+        var filePath = Path.Combine(dir, $"rollout-test-{sessionId}.jsonl");
+
+        try
+        {
+            File.WriteAllLines(
+                filePath,
+                new[]
+                {
+                    BuildSessionMetaLine(sessionId),
+                    BuildUserMessageLine("first"),
+                    BuildAssistantMessageLine("first reply"),
+                    BuildUserMessageLine("second"),
+                    BuildAssistantMessageLine("second reply"),
+                },
+                new UTF8Encoding(encoderShouldEmitUTF8Identifier: false));
+
+            var store = CreateStore();
+            var latest = store.ReadMessages(sessionId, limit: 2)!.ToArray();
+            var older = store.ReadMessages(sessionId, limit: 2, before: latest[0].Ordinal)!.ToArray();
+
+            Assert.Equal(new[] { 2, 3 }, latest.Select(m => m.Ordinal).ToArray());
+            Assert.Equal("second", latest[0].Text);
+            Assert.Equal(new[] { 0, 1 }, older.Select(m => m.Ordinal).ToArray());
+            Assert.Equal("first", older[0].Text);
+            Assert.Equal("first reply", older[1].Text);
+        }
+        finally
+        {
+            TryDeleteFile(filePath);
+            ClearTempSessionsRoot(sessionsRoot);
+        }
+    }
+
     [Fact]
     public void TryReadLatestSettings_ReadsLatestApprovalPolicyAndSandbox()
     {
@@ -267,7 +425,7 @@ public sealed class CodexSessionStoreTests
         return $"{{\"timestamp\":\"{DateTimeOffset.UtcNow:O}\",\"type\":\"event_msg\",\"payload\":{{{payload}}}}}";
     }
 
-    private static string JsonString(string value) =>
+    private static string JsonString<T>(T value) =>
         System.Text.Json.JsonSerializer.Serialize(value);
 
     private static void TryDeleteFile(string filePath)
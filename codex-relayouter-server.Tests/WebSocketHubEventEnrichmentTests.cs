@@ -61,5 +61,67 @@ public class WebSocketHubEventEnrichmentTests
         Assert.True(enriched.Data.TryGetProperty("sessionId", out var sessionId));
         Assert.Equal("existing", sessionId.GetString());
     }
-}
 
+    [Fact]
+    public void ParseExecpolicyAmendment_keepsNonBlankStrings()
+    {
+        var data = JsonSerializer.SerializeToElement(
+            new { runId = "r1", execpolicyAmendment = new object?[] { "cargo", " ", 7, null, "test" } },
+            WebJsonOptions);
+
+        Assert.Equal(new[] { "cargo", "test" }, WebSocketHub.ParseExecpolicyAmendment(data));
+    }
+
+    [Fact]
+    public void ParseExecpolicyAmendment_returnsNullWhenMissingOrEmpty()
+    {
+        var missing = JsonSerializer.SerializeToElement(new { runId = "r1" }, WebJsonOptions);
+        var empty = JsonSerializer.SerializeToElement(new { execpolicyAmendment = Array.Empty<string>() }, WebJsonOptions);
+        var notArray = JsonSerializer.SerializeToElement(new { execpolicyAmendment = "cargo test" }, WebJsonOptions);
+
+        Assert.Null(WebSocketHub.ParseExecpolicyAmendment(missing));
+        Assert.Null(WebSocketHub.ParseExecpolicyAmendment(empty));
+        Assert.Null(WebSocketHub.ParseExecpolicyAmendment(notArray));
+    }
+
+    [Fact]
+    public void ApprovalRequestedPayload_carriesCommandAndCwd()
+    {
+        var @params = JsonSerializer.SerializeToElement(
+            new
+            {
+                threadId = "t1",
+                turnId = "u1",
+                itemId = "i1",
+                command = new[] { "cargo", "test" },
+                cwd = "/repo",
+                proposedExecpolicyAmendment = new[] { "cargo" },
+            },
+            WebJsonOptions);
+        var approval = CodexAppServerRunner.ParseApprovalRequest(42, kind: "commandExecution", @params);
+
+        var payload = JsonSerializer.SerializeToElement(
+            WebSocketHub.BuildApprovalRequestedPayload("r1", approval.RequestId, approval),
+            WebJsonOptions);
+
+        Assert.Equal("42", payload.GetProperty("requestId").GetString());
+        Assert.Equal("commandExecution", payload.GetProperty("kind").GetString());
+        Assert.Equal("cargo test", payload.GetProperty("command").GetString());
+        Assert.Equal("/repo", payload.GetProperty("cwd").GetString());
+        Assert.Equal("cargo", payload.GetProperty("proposedExecpolicyAmendment")[0].GetString());
+    }
+
+    [Fact]
+    public void ApprovalRequestedPayload_leavesCommandAndCwdNullForFileChanges()
+    {
+        var @params = JsonSerializer.SerializeToElement(new { threadId = "t1", turnId = "u1", reason = "write" }, WebJsonOptions);
+        var approval = CodexAppServerRunner.ParseApprovalRequest(7, kind: "fileChange", @params);
+
+        var payload = JsonSerializer.SerializeToElement(
+            WebSocketHub.BuildApprovalRequestedPayload("r1", approval.RequestId, approval),
+            WebJsonOptions);
+
+        Assert.Equal(JsonValueKind.Null, payload.GetProperty("command").ValueKind);
+        Assert.Equal(JsonValueKind.Null, payload.GetProperty("cwd").ValueKind);
+    }
+}
@@ -17,5 +17,9 @@ public sealed class CodexAppServerApprovalRequest
     public string[]? ProposedExecpolicyAmendment { get; init; }
 
     public string? GrantRoot { get; init; }
+
+    public string? Command { get; init; }
+
+    public string? Cwd { get; init; }
 }
 
@@ -1110,13 +1110,31 @@ public sealed class CodexAppServerRunner
         }
     }
 
-    private static CodexAppServerApprovalRequest ParseApprovalRequest(long requestId, string kind, JsonElement @params)
+    internal static CodexAppServerApprovalRequest ParseApprovalRequest(long requestId, string kind, JsonElement @params)
     {
         TryGetString(@params, "threadId", out var threadId);
         TryGetString(@params, "turnId", out var turnId);
         TryGetString(@params, "itemId", out var itemId);
         TryGetString(@params, "reason", out var reason);
         TryGetString(@params, "grantRoot", out var grantRoot);
+        TryGetString(@params, "cwd", out var cwd);
+
+        string? command = null;
+        if (@params.TryGetProperty("command", out var commandProp))
+        {
+            if (commandProp.ValueKind == JsonValueKind.String)
+            {
+                command = commandProp.GetString();
+            }
+            else if (commandProp.ValueKind == JsonValueKind.Array)
+            {
+                command = string.Join(
+                    ' ',
+                    commandProp.EnumerateArray()
+                        .Where(part => part.ValueKind == JsonValueKind.String)
+                        .Select(part => part.GetString()));
+            }
+        }
 
         string[]? proposed = null;
         if (@params.TryGetProperty("proposedExecpolicyAmendment", out var proposedProp) && proposedProp.ValueKind == JsonValueKind.Array)
@@ -1149,6 +1167,8 @@ public sealed class CodexAppServerRunner
             Reason = reason,
             ProposedExecpolicyAmendment = proposed,
             GrantRoot = grantRoot,
+            Command = string.IsNullOrWhiteSpace(command) ? null : command.Trim(),
+            Cwd = string.IsNullOrWhiteSpace(cwd) ? null : cwd,
         };
     }
 
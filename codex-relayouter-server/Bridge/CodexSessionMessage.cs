@@ -5,6 +5,11 @@ namespace codex_bridge_server.Bridge;
 
 public sealed class CodexSessionMessage
 {
+    /// <summary>
+    /// 消息在整个会话中的序号（从 0 开始），会话只追加写入，因此同一条消息的序号保持不变。
+    /// </summary>
+    public int Ordinal { get; init; }
+
     public required string Role { get; init; }
 
     public required string Text { get; init; }
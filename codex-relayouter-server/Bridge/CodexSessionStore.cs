@@ -21,9 +21,10 @@ public sealed class CodexSessionStore
         _cliInfo = cliInfo;
     }
 
-    public IReadOnlyList<CodexSessionSummary> ListRecent(int limit)
+    public IReadOnlyList<CodexSessionSummary> ListRecent(int limit, int offset = 0)
     {
         limit = Math.Clamp(limit, 1, 200);
+        offset = Math.Max(offset, 0);
 
         var sessionsRoot = GetSessionsRoot();
         if (!Directory.Exists(sessionsRoot))
@@ -53,7 +54,9 @@ public sealed class CodexSessionStore
 
         fileInfos.Sort(static (a, b) => b.LastWriteTimeUtc.CompareTo(a.LastWriteTimeUtc));
 
+        // offset 按有效会话计数，被过滤的会话不占位置，分页才不会漏项。
         var results = new List<CodexSessionSummary>(limit);
+        var skipped = 0;
         foreach (var fi in fileInfos)
         {
             if (results.Count >= limit)
@@ -62,10 +65,18 @@ public sealed class CodexSessionStore
             }
 
             var summary = TryReadSessionMeta(fi.FullName);
-            if (summary is not null)
+            if (summary is null)
             {
-                results.Add(summary);
+                continue;
             }
+
+            if (skipped < offset)
+            {
+                skipped++;
+                continue;
+            }
+
+            results.Add(summary);
         }
 
         return results;
@@ -117,6 +128,7 @@ public sealed class CodexSessionStore
             Id = sessionId,
             Title = BuildSessionTitle(firstUserMessageText: null, metaLine.Payload.Cwd, sessionId),
             CreatedAt = now,
+            UpdatedAt = now,
             Cwd = metaLine.Payload.Cwd,
             Originator = metaLine.Payload.Originator,
             CliVersion = metaLine.Payload.CliVersion,
@@ -256,7 +268,7 @@ public sealed class CodexSessionStore
         }
     }
 
-    public IReadOnlyList<CodexSessionMessage>? ReadMessages(string sessionId, int limit)
+    public IReadOnlyList<CodexSessionMessage>? ReadMessages(string sessionId, int limit, int? before = null)
     {
         if (string.IsNullOrWhiteSpace(sessionId))
         {
@@ -286,6 +298,24 @@ public sealed class CodexSessionStore
             var traceBuffer = new List<CodexSessionTraceEntry>(capacity: 16);
             var traceByCallId = new Dictionary<string, CodexSessionTraceEntry>(StringComparer.Ordinal);
             string? pendingAgentMessage = null;
+            var nextOrdinal = 0;
+
+            // 序号按消息在会话中的出现顺序编号，与 limit/before 无关，客户端据此翻页并生成稳定 id。
+            void Enqueue(CodexSessionMessage message)
+            {
+                nextOrdinal++;
+                if (before is not null && message.Ordinal >= before)
+                {
+                    return;
+                }
+
+                if (queue.Count >= limit)
+                {
+                    queue.Dequeue();
+                }
+
+                queue.Enqueue(message);
+            }
 
             void FlushPendingAssistantMessage()
             {
@@ -298,13 +328,9 @@ public sealed class CodexSessionStore
                     ? PlaceholderAssistantText
                     : pendingAgentMessage.Trim();
 
-                if (queue.Count >= limit)
-                {
-                    queue.Dequeue();
-                }
-
-                queue.Enqueue(new CodexSessionMessage
+                Enqueue(new CodexSessionMessage
                 {
+                    Ordinal = nextOrdinal,
                     Role = "assistant",
                     Text = text,
                     Trace = traceBuffer.Count > 0 ? traceBuffer.ToArray() : null,
@@ -356,13 +382,14 @@ public sealed class CodexSessionStore
                         continue;
                     }
 
-                    if (TryParseFunctionCallLine(line, out var callId, out var tool, out var command))
+                    if (TryParseFunctionCallLine(line, out var callId, out var tool, out var command, out var patch))
                     {
                         var entry = new CodexSessionTraceEntry
                         {
                             Kind = "command",
                             Tool = tool,
                             Command = command,
+                            Patch = patch,
                             Status = "completed",
                         };
                         traceBuffer.Add(entry);
@@ -439,13 +466,9 @@ public sealed class CodexSessionStore
                     }
                 }
 
-                if (queue.Count >= limit)
-                {
-                    queue.Dequeue();
-                }
-
-                queue.Enqueue(new CodexSessionMessage
+                Enqueue(new CodexSessionMessage
                 {
+                    Ordinal = nextOrdinal,
                     Role = role,
                     Text = text,
                     Images = images,
@@ -763,11 +786,12 @@ public sealed class CodexSessionStore
         }
     }
 
-    private static bool TryParseFunctionCallLine(string line, out string? callId, out string tool, out string command)
+    private static bool TryParseFunctionCallLine(string line, out string? callId, out string tool, out string command, out string? patch)
     {
         callId = null;
         tool = string.Empty;
         command = string.Empty;
+        patch = null;
 
         if (string.IsNullOrWhiteSpace(line))
         {
@@ -805,6 +829,7 @@ public sealed class CodexSessionStore
             if (TryGetString(payload, "arguments", out var args) && !string.IsNullOrWhiteSpace(args))
             {
                 command = BuildCommandLabel(tool, args);
+                patch = TryGetPatchInput(tool, args);
             }
 
             if (string.IsNullOrWhiteSpace(command))
@@ -820,6 +845,40 @@ public sealed class CodexSessionStore
         }
     }
 
+    // apply_patch 的补丁原文：回放时客户端据此还原逐文件 diff。
+    private static string? TryGetPatchInput(string tool, string arguments)
+    {
+        if (!string.Equals(tool, "apply_patch", StringComparison.OrdinalIgnoreCase))
+        {
+            return null;
+        }
+
+        if (arguments.TrimStart().StartsWith("*** Begin Patch", StringComparison.Ordinal))
+        {
+            return arguments;
+        }
+
+        try
+        {
+            using var doc = JsonDocument.Parse(arguments);
+            var root = doc.RootElement;
+            if (TryGetString(root, "input", out var input) && !string.IsNullOrWhiteSpace(input))
+            {
+                return input;
+            }
+
+            if (TryGetString(root, "patch", out var patchText) && !string.IsNullOrWhiteSpace(patchText))
+            {
+                return patchText;
+            }
+        }
+        catch (JsonException)
+        {
+        }
+
+        return null;
+    }
+
     private static string BuildCommandLabel(string tool, string arguments)
     {
         if (string.Equals(tool, "shell_command", StringComparison.OrdinalIgnoreCase))
@@ -993,6 +1052,7 @@ public sealed class CodexSessionStore
                 CreatedAt = createdAt == DateTimeOffset.MinValue
                     ? new DateTimeOffset(File.GetLastWriteTimeUtc(filePath))
                     : createdAt,
+                UpdatedAt = new DateTimeOffset(File.GetLastWriteTimeUtc(filePath)),
                 Cwd = string.IsNullOrWhiteSpace(cwd) ? null : cwd,
                 Originator = string.IsNullOrWhiteSpace(originator) ? null : originator,
                 CliVersion = string.IsNullOrWhiteSpace(cliVersion) ? null : cliVersion,
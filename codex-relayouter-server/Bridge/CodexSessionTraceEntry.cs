@@ -27,4 +27,10 @@ public sealed class CodexSessionTraceEntry
 
     [JsonIgnore(Condition = JsonIgnoreCondition.WhenWritingNull)]
     public string? Output { get; set; }
+
+    /// <summary>
+    /// apply_patch 调用的补丁原文，仅 apply_patch 条目有值。
+    /// </summary>
+    [JsonIgnore(Condition = JsonIgnoreCondition.WhenWritingNull)]
+    public string? Patch { get; set; }
 }
@@ -199,13 +199,13 @@ public sealed class WebSocketHub
         }
         catch (JsonException)
         {
-            await BroadcastAsync(CreateEvent("bridge.error", new { message = "无效 JSON" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("bridge.error", new { clientId, message = "无效 JSON" }), cancellationToken);
             return;
         }
 
         if (envelope is null)
         {
-            await BroadcastAsync(CreateEvent("bridge.error", new { message = "空消息" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("bridge.error", new { clientId, message = "空消息" }), cancellationToken);
             return;
         }
 
@@ -237,7 +237,7 @@ public sealed class WebSocketHub
 
         if (string.IsNullOrWhiteSpace(prompt) && (images is null || images.Length == 0))
         {
-            await BroadcastAsync(CreateEvent("run.rejected", new { reason = "缺少 prompt/images" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "chat.send", reason = "缺少 prompt/images" }), cancellationToken);
             return;
         }
 
@@ -266,7 +266,7 @@ public sealed class WebSocketHub
         {
             _runs.TryRemove(runId, out _);
             cts.Dispose();
-            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, sessionId, reason = "该会话已有运行中的任务" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "chat.send", sessionId, reason = "该会话已有运行中的任务" }), cancellationToken);
             return;
         }
 
@@ -371,7 +371,7 @@ public sealed class WebSocketHub
 
         if (string.IsNullOrWhiteSpace(runId) && string.IsNullOrWhiteSpace(sessionId))
         {
-            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, reason = "缺少 runId/sessionId" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "run.cancel", reason = "缺少 runId/sessionId" }), cancellationToken);
             return;
         }
 
@@ -382,12 +382,13 @@ public sealed class WebSocketHub
 
         if (string.IsNullOrWhiteSpace(runId) || !_runs.TryGetValue(runId, out var run))
         {
-            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, sessionId, reason = "没有可取消的任务" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "run.cancel", sessionId, reason = "没有可取消的任务" }), cancellationToken);
             return;
         }
 
         run.Cts.Cancel();
-        await BroadcastAsync(CreateEvent("run.cancel.requested", new { clientId, runId, sessionId = run.SessionId ?? sessionId }), cancellationToken);
+        var deviceId = _clients.TryGetValue(clientId, out var sender) ? sender.DeviceId : null;
+        await BroadcastAsync(CreateEvent("run.cancel.requested", new { clientId, deviceId, runId, sessionId = run.SessionId ?? sessionId }), cancellationToken);
     }
 
     private async Task EmitRunEventAsync(RunContext run, BridgeEnvelope envelope, CancellationToken cancellationToken)
@@ -633,20 +634,7 @@ public sealed class WebSocketHub
             });
 
         await BroadcastAsync(
-            CreateEvent(
-                "approval.requested",
-                new
-                {
-                    runId,
-                    requestId,
-                    kind = approval.Kind,
-                    threadId = approval.ThreadId,
-                    turnId = approval.TurnId,
-                    itemId = approval.ItemId,
-                    reason = approval.Reason,
-                    proposedExecpolicyAmendment = approval.ProposedExecpolicyAmendment,
-                    grantRoot = approval.GrantRoot,
-                }),
+            CreateEvent("approval.requested", BuildApprovalRequestedPayload(runId, requestId, approval)),
             cancellationToken);
 
         using var reg = cancellationToken.Register(() => tcs.TrySetCanceled(cancellationToken));
@@ -664,7 +652,7 @@ public sealed class WebSocketHub
     {
         if (!TryGetString(envelope.Data, "runId", out var runId) || string.IsNullOrWhiteSpace(runId))
         {
-            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, reason = "缺少 runId" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "approval.respond", reason = "缺少 runId" }), cancellationToken);
             return;
         }
 
@@ -672,7 +660,7 @@ public sealed class WebSocketHub
 
         if (!TryGetString(envelope.Data, "requestId", out var requestId) || string.IsNullOrWhiteSpace(requestId))
         {
-            await BroadcastAsync(CreateEvent("run.rejected", new { reason = "缺少 requestId" }), cancellationToken);
+            await BroadcastAsync(CreateEvent("run.rejected", new { clientId, command = "approval.respond", runId, reason = "缺少 requestId" }), cancellationToken);
             return;
         }
 
@@ -689,7 +677,11 @@ public sealed class WebSocketHub
             return;
         }
 
-        tcs.TrySetResult(new CodexAppServerApprovalDecision { Decision = decision });
+        tcs.TrySetResult(new CodexAppServerApprovalDecision
+        {
+            Decision = decision,
+            ExecpolicyAmendment = ParseExecpolicyAmendment(envelope.Data),
+        });
 
         await BroadcastAsync(CreateEvent("approval.responded", new { clientId, requestId, decision }), cancellationToken);
     }
@@ -943,6 +935,46 @@ public sealed class WebSocketHub
             : null;
     }
 
+    // approval.requested 的负载：command/cwd 供客户端展示并按会话规则自动批准。
+    internal static object BuildApprovalRequestedPayload(string runId, string requestId, CodexAppServerApprovalRequest approval) =>
+        new
+        {
+            runId,
+            requestId,
+            kind = approval.Kind,
+            threadId = approval.ThreadId,
+            turnId = approval.TurnId,
+            itemId = approval.ItemId,
+            reason = approval.Reason,
+            proposedExecpolicyAmendment = approval.ProposedExecpolicyAmendment,
+            grantRoot = approval.GrantRoot,
+            command = approval.Command,
+            cwd = approval.Cwd,
+        };
+
+    // 客户端选择“始终允许此前缀”时附带的命令前缀（acceptWithExecpolicyAmendment）。
+    internal static string[]? ParseExecpolicyAmendment(JsonElement data)
+    {
+        if (data.ValueKind != JsonValueKind.Object
+            || !data.TryGetProperty("execpolicyAmendment", out var amendmentProp)
+            || amendmentProp.ValueKind != JsonValueKind.Array)
+        {
+            return null;
+        }
+
+        var list = new List<string>();
+        foreach (var item in amendmentProp.EnumerateArray())
+        {
+            var text = item.ValueKind == JsonValueKind.String ? item.GetString() : null;
+            if (!string.IsNullOrWhiteSpace(text))
+            {
+                list.Add(text);
+            }
+        }
+
+        return list.Count == 0 ? null : list.ToArray();
+    }
+
     private static string[]? ParseImageDataUrls(JsonElement data)
     {
         if (data.ValueKind != JsonValueKind.Object)
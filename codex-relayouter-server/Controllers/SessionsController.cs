@@ -26,14 +26,14 @@ public sealed class SessionsController : ControllerBase
     }
 
     [HttpGet]
-    public IActionResult List([FromQuery] int? limit)
+    public IActionResult List([FromQuery] int? limit, [FromQuery] int? offset)
     {
         if (!_authorizer.IsAuthorized(HttpContext))
         {
             return Unauthorized();
         }
 
-        var sessions = _sessionStore.ListRecent(limit ?? 30);
+        var sessions = _sessionStore.ListRecent(limit ?? 30, offset ?? 0);
         return Ok(sessions);
     }
 
@@ -68,7 +68,7 @@ public sealed class SessionsController : ControllerBase
     }
 
     [HttpGet("{sessionId}/messages")]
-    public IActionResult GetMessages([FromRoute] string sessionId, [FromQuery] int? limit)
+    public IActionResult GetMessages([FromRoute] string sessionId, [FromQuery] int? limit, [FromQuery] int? before)
     {
         if (!_authorizer.IsAuthorized(HttpContext))
         {
@@ -80,7 +80,7 @@ public sealed class SessionsController : ControllerBase
             return BadRequest(new { message = "sessionId 不能为空" });
         }
 
-        var messages = _sessionStore.ReadMessages(sessionId, limit ?? 200);
+        var messages = _sessionStore.ReadMessages(sessionId, limit ?? 200, before);
         if (messages is null)
         {
             return NotFound();
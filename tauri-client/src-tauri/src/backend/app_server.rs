@@ -0,0 +1,105 @@
+// This backend forwards requests to a `codex app-server` child process unchanged.
+
+use futures_util::future::BoxFuture;
+use serde_json::{json, Map, Value};
+
+use super::{BackendHost, BackendResult, McpBackend, McpCall};
+use crate::app_server::{
+    is_method_not_found, request, respond_to_server_request, rpc_error_message,
+};
+use crate::AppState;
+
+pub(super) struct AppServerBackend;
+
+async fn forward_call(call: McpCall<'_>, method: &str) -> Result<Value, Value> {
+    let params = call
+        .params
+        .cloned()
+        .map(Value::Object)
+        .unwrap_or(Value::Null);
+    let app = call
+        .host
+        .app_handle()
+        .map_err(|message| json!({ "message": message }))?;
+    request(app, call.state, method, params).await
+}
+
+async fn call_method(call: McpCall<'_>, method: &str) -> BackendResult {
+    forward_call(call, method)
+        .await
+        .map_err(|error| format!("{method} failed: {}", rpc_error_message(&error)))
+}
+
+impl McpBackend for AppServerBackend {
+    fn list_threads<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "thread/list"))
+    }
+
+    fn read_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "thread/read"))
+    }
+
+    fn start_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "thread/start"))
+    }
+
+    fn resume_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "thread/resume"))
+    }
+
+    fn set_thread_archived<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        archived: bool,
+    ) -> BoxFuture<'a, BackendResult> {
+        let method = if archived {
+            "thread/archive"
+        } else {
+            "thread/unarchive"
+        };
+        Box::pin(call_method(call, method))
+    }
+
+    fn delete_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "thread/delete"))
+    }
+
+    fn start_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "turn/start"))
+    }
+
+    fn interrupt_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "turn/interrupt"))
+    }
+
+    fn list_models<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(call_method(call, "model/list"))
+    }
+
+    fn respond_to_approval<'a>(
+        &'a self,
+        _host: &'a dyn BackendHost,
+        state: &'a AppState,
+        response: &'a Map<String, Value>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async move { respond_to_server_request(state, response) })
+    }
+
+    // Anything the app-server does not implement falls back to the host's own answers.
+    fn forward<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        method: &'a str,
+    ) -> BoxFuture<'a, Option<BackendResult>> {
+        Box::pin(async move {
+            match forward_call(call, method).await {
+                Ok(result) => Some(Ok(result)),
+                Err(error) if is_method_not_found(&error) => None,
+                Err(error) => Some(Err(format!(
+                    "{method} failed: {}",
+                    rpc_error_message(&error)
+                ))),
+            }
+        })
+    }
+}
@@ -0,0 +1,559 @@
+// This backend serves threads and turns from the .NET bridge over HTTP and WebSocket.
+
+use futures_util::future::BoxFuture;
+use serde_json::{json, Map, Value};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use super::{BackendHost, BackendResult, McpBackend, McpCall};
+use crate::bridge_protocol::CAPABILITY_CHAT_IMAGES;
+use crate::{approvals, attachments};
+use crate::{
+    bridge_delete_json, bridge_list_sessions, bridge_post_json, bridge_read_thread,
+    bridge_session_path, bridge_session_settings, bridge_summary_to_thread_list_json,
+    bridge_supports, bridge_thread_turns_page, extract_approval_policy_from_params,
+    extract_effort_from_params, extract_model_from_params, extract_sandbox_from_params, git,
+    local_app_data_dir, lock_or_err, normalize_root_string, preferred_workspace_cwd,
+    queue_pending_turn, read_codex_config_snapshot, send_bridge_command, turn_snapshots,
+    unqueue_pending_turn, AppState, BRIDGE_HISTORY_PAGE_SIZE, BRIDGE_SESSION_LIST_PAGE_SIZE,
+    DEFAULT_APPROVAL_POLICY, DEFAULT_MODEL, DEFAULT_REASONING_EFFORT, DEFAULT_SANDBOX_MODE,
+};
+
+const ARCHIVED_SESSIONS_FILE_NAME: &str = "archived_sessions.json";
+
+// The bridge keeps no archive flag, so archived session ids live in a host-side file.
+#[derive(Default)]
+pub(super) struct BridgeBackend {
+    archived: Mutex<Option<HashSet<String>>>,
+}
+
+fn archived_sessions_path() -> Option<PathBuf> {
+    Some(
+        local_app_data_dir()?
+            .join("codex-relayouter")
+            .join(ARCHIVED_SESSIONS_FILE_NAME),
+    )
+}
+
+fn read_archived_session_ids() -> HashSet<String> {
+    let Some(content) = archived_sessions_path().and_then(|path| fs::read_to_string(path).ok())
+    else {
+        return HashSet::new();
+    };
+    serde_json::from_str::<Value>(&content)
+        .ok()
+        .and_then(|json| json.get("archivedSessionIds").cloned())
+        .and_then(|ids| serde_json::from_value::<HashSet<String>>(ids).ok())
+        .unwrap_or_default()
+}
+
+fn write_archived_session_ids(ids: &HashSet<String>) -> Result<(), String> {
+    let path = archived_sessions_path()
+        .ok_or_else(|| "Could not resolve archived sessions directory".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create archived sessions directory: {e}"))?;
+    }
+    let mut sorted = ids.iter().collect::<Vec<_>>();
+    sorted.sort();
+    let body = serde_json::to_string_pretty(&json!({ "archivedSessionIds": sorted }))
+        .map_err(|e| format!("Failed to serialize archived sessions: {e}"))?;
+    fs::write(&path, body).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+impl BridgeBackend {
+    fn with_archived<T>(
+        &self,
+        update: impl FnOnce(&mut HashSet<String>) -> T,
+    ) -> Result<T, String> {
+        let mut guard = lock_or_err(&self.archived, "archived_sessions")?;
+        let archived = guard.get_or_insert_with(read_archived_session_ids);
+        Ok(update(archived))
+    }
+
+    fn set_archived(&self, thread_id: &str, archived: bool) -> Result<(), String> {
+        self.with_archived(|ids| {
+            let changed = if archived {
+                ids.insert(thread_id.to_string())
+            } else {
+                ids.remove(thread_id)
+            };
+            if changed {
+                write_archived_session_ids(ids)
+            } else {
+                Ok(())
+            }
+        })?
+    }
+}
+
+fn requested_cwd(call: &McpCall<'_>) -> Option<String> {
+    call.str_param("cwd").map(normalize_root_string)
+}
+
+async fn list_threads(backend: &BridgeBackend, call: McpCall<'_>) -> BackendResult {
+    let archived = call
+        .params
+        .and_then(|p| p.get("archived"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let limit = call
+        .params
+        .and_then(|p| p.get("limit"))
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(100)
+        .clamp(1, 200);
+    let offset = call
+        .str_param("cursor")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    // Archive state is filtered host-side, so page through the bridge until this page is covered.
+    // One extra match tells whether another page follows.
+    let archived_ids = backend.with_archived(|ids| ids.clone())?;
+    let wanted = offset + limit + 1;
+    let mut seen = HashSet::new();
+    let mut summaries = Vec::new();
+    let mut bridge_offset = 0;
+    while summaries.len() < wanted {
+        let batch = bridge_list_sessions(call.state, bridge_offset, BRIDGE_SESSION_LIST_PAGE_SIZE)
+            .await
+            .map_err(|error| format!("thread/list failed: {error}"))?;
+        let exhausted = batch.len() < BRIDGE_SESSION_LIST_PAGE_SIZE;
+        bridge_offset += batch.len();
+        let before = seen.len();
+        summaries.extend(
+            batch
+                .into_iter()
+                .filter(|summary| seen.insert(summary.id.clone()))
+                .filter(|summary| archived_ids.contains(&summary.id) == archived)
+                .map(|summary| (summary.timestamps(call.state).1, summary)),
+        );
+        // Bridges without `offset` repeat the first page; nothing new means nothing further.
+        if exhausted || seen.len() == before {
+            break;
+        }
+    }
+    summaries.sort_by_key(|(updated_at, _)| Reverse(*updated_at));
+    let total = summaries.len();
+    let page = summaries
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, summary)| summary)
+        .collect::<Vec<_>>();
+    let cwds = page
+        .iter()
+        .filter_map(|summary| summary.cwd.clone())
+        .collect::<Vec<_>>();
+    let git_infos = git::git_info_by_cwd(call.state, &cwds).await;
+    let data = page
+        .iter()
+        .map(|summary| {
+            let git_info = summary
+                .cwd
+                .as_ref()
+                .and_then(|cwd| git_infos.get(cwd).cloned())
+                .unwrap_or(Value::Null);
+            bridge_summary_to_thread_list_json(call.state, summary, git_info)
+        })
+        .collect::<Vec<_>>();
+    let next_cursor = if offset + data.len() < total {
+        Value::String((offset + data.len()).to_string())
+    } else {
+        Value::Null
+    };
+    Ok(json!({ "data": data, "nextCursor": next_cursor }))
+}
+
+async fn read_thread(call: McpCall<'_>) -> BackendResult {
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/read requires threadId".to_string());
+    };
+    let include_turns = call
+        .params
+        .and_then(|p| p.get("includeTurns"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    let thread = bridge_read_thread(call.state, thread_id, include_turns, requested_cwd(&call))
+        .await
+        .map_err(|error| format!("thread/read failed: {error}"))?;
+    Ok(json!({ "thread": thread }))
+}
+
+// Loads older turns as the view scrolls back through a long session.
+async fn list_thread_turns(call: McpCall<'_>) -> BackendResult {
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/turns/list requires threadId".to_string());
+    };
+    let limit = call
+        .params
+        .and_then(|p| p.get("limit"))
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(BRIDGE_HISTORY_PAGE_SIZE);
+
+    let (data, next_cursor) =
+        bridge_thread_turns_page(call.state, thread_id, call.str_param("cursor"), limit)
+            .await
+            .map_err(|error| format!("thread/turns/list failed: {error}"))?;
+    Ok(json!({ "data": data, "nextCursor": next_cursor }))
+}
+
+async fn start_thread(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let cwd = requested_cwd(&call).unwrap_or_else(|| preferred_workspace_cwd(state));
+    let config = read_codex_config_snapshot();
+    let model = extract_model_from_params(call.params)
+        .or_else(|| config.model.clone())
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let reasoning_effort = extract_effort_from_params(call.params)
+        .or_else(|| config.model_reasoning_effort.clone())
+        .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
+    let approval_policy = extract_approval_policy_from_params(call.params)
+        .or_else(|| config.approval_policy.clone())
+        .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
+    let sandbox_mode = extract_sandbox_from_params(call.params)
+        .or_else(|| config.sandbox_mode.clone())
+        .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
+
+    let created = bridge_post_json(state, "api/v1/sessions", json!({ "cwd": cwd }))
+        .await
+        .map_err(|error| format!("thread/start failed: {error}"))?;
+    let thread_id = created
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if thread_id.is_empty() {
+        return Err("thread/start failed: missing session id".to_string());
+    }
+
+    let thread_json = bridge_read_thread(state, &thread_id, true, Some(cwd.clone()))
+        .await
+        .map_err(|error| format!("thread/start read failed: {error}"))?;
+    Ok(json!({
+        "thread": thread_json,
+        "model": model,
+        "reasoningEffort": reasoning_effort,
+        "cwd": cwd,
+        "sessionMeta": {
+            "cwd": cwd,
+            "approvalPolicy": approval_policy,
+            "sandboxMode": sandbox_mode
+        }
+    }))
+}
+
+async fn resume_thread(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/resume requires threadId".to_string());
+    };
+    let config = read_codex_config_snapshot();
+    let model = extract_model_from_params(call.params)
+        .or_else(|| config.model.clone())
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let reasoning_effort = extract_effort_from_params(call.params)
+        .or_else(|| config.model_reasoning_effort.clone())
+        .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
+    let (session_approval, session_sandbox) = bridge_session_settings(state, thread_id).await;
+
+    let thread_json = bridge_read_thread(state, thread_id, true, requested_cwd(&call))
+        .await
+        .map_err(|error| format!("thread/resume failed: {error}"))?;
+    let cwd = thread_json
+        .get("cwd")
+        .and_then(Value::as_str)
+        .unwrap_or("/")
+        .to_string();
+    Ok(json!({
+        "thread": thread_json,
+        "model": model,
+        "reasoningEffort": reasoning_effort,
+        "cwd": cwd,
+        "sessionMeta": {
+            "cwd": cwd,
+            "approvalPolicy": session_approval.unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string()),
+            "sandboxMode": session_sandbox.unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string())
+        }
+    }))
+}
+
+async fn start_turn(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let Some(thread_id) = call.str_param("threadId").map(str::to_string) else {
+        return Err("turn/start requires threadId".to_string());
+    };
+    let input = call
+        .params
+        .and_then(|p| p.get("input"))
+        .cloned()
+        .unwrap_or_else(|| json!([]));
+    let cwd = requested_cwd(&call).unwrap_or_else(|| preferred_workspace_cwd(state));
+    let prompt = attachments::prompt_from_input(&input, &cwd).await;
+    let images = attachments::image_data_urls_from_input(&input, &cwd)
+        .await
+        .map_err(|error| format!("turn/start failed: {error}"))?;
+    if prompt.is_empty() && images.is_empty() {
+        return Err("turn/start requires non-empty input".to_string());
+    }
+    if !images.is_empty() && !bridge_supports(state, CAPABILITY_CHAT_IMAGES) {
+        return Err("turn/start failed: the bridge server does not accept images".to_string());
+    }
+
+    let config = read_codex_config_snapshot();
+    let model = extract_model_from_params(call.params)
+        .or_else(|| config.model.clone())
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let effort = extract_effort_from_params(call.params)
+        .or_else(|| config.model_reasoning_effort.clone())
+        .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
+    let (session_approval, session_sandbox) = bridge_session_settings(state, &thread_id).await;
+    let approval_policy = extract_approval_policy_from_params(call.params)
+        .or(session_approval)
+        .or_else(|| config.approval_policy.clone())
+        .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
+    let sandbox_mode = extract_sandbox_from_params(call.params)
+        .or(session_sandbox)
+        .or_else(|| config.sandbox_mode.clone())
+        .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
+    let turn_id = Uuid::new_v4().to_string();
+
+    turn_snapshots::begin_turn(state, &thread_id, &turn_id, &cwd);
+    if let Err(error) = queue_pending_turn(state, &thread_id, &turn_id) {
+        turn_snapshots::discard_turn(state, &turn_id);
+        return Err(format!("turn/start queue failed: {error}"));
+    }
+
+    let mut command = json!({
+        "prompt": prompt,
+        "sessionId": thread_id,
+        "workingDirectory": cwd,
+        "model": model,
+        "sandbox": sandbox_mode,
+        "approvalPolicy": approval_policy,
+        "effort": effort,
+        "skipGitRepoCheck": true
+    });
+    if !images.is_empty() {
+        if let Value::Object(obj) = &mut command {
+            obj.insert(
+                "images".to_string(),
+                Value::Array(images.into_iter().map(Value::String).collect::<Vec<_>>()),
+            );
+        }
+    }
+
+    if let Err(error) =
+        send_bridge_command(call.host.app_handle()?, state, "chat.send", command).await
+    {
+        let _ = unqueue_pending_turn(state, &thread_id, &turn_id);
+        turn_snapshots::discard_turn(state, &turn_id);
+        return Err(format!("turn/start failed: {error}"));
+    }
+    Ok(json!({
+        "threadId": thread_id,
+        "turn": {
+            "id": turn_id,
+            "status": "inProgress",
+            "error": Value::Null
+        }
+    }))
+}
+
+async fn revert_turn(call: McpCall<'_>) -> BackendResult {
+    let Some(turn_id) = call.str_param("turnId") else {
+        return Err("turn/revert requires turnId".to_string());
+    };
+    turn_snapshots::revert_turn(call.state, turn_id)
+        .await
+        .map_err(|error| format!("turn/revert failed: {error}"))
+}
+
+async fn interrupt_turn(call: McpCall<'_>) -> BackendResult {
+    let mut data = Map::new();
+    if let Some(run_id) = call.str_param("runId") {
+        data.insert("runId".to_string(), Value::String(run_id.to_string()));
+    }
+    if let Some(thread_id) = call
+        .str_param("threadId")
+        .or_else(|| call.str_param("sessionId"))
+    {
+        data.insert(
+            "sessionId".to_string(),
+            Value::String(thread_id.to_string()),
+        );
+    }
+    send_bridge_command(
+        call.host.app_handle()?,
+        call.state,
+        "run.cancel",
+        Value::Object(data),
+    )
+    .await
+    .map_err(|error| format!("turn/interrupt failed: {error}"))?;
+    Ok(json!({ "success": true }))
+}
+
+fn list_models() -> BackendResult {
+    let config = read_codex_config_snapshot();
+    let default_model = config.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let default_effort = config
+        .model_reasoning_effort
+        .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
+    Ok(json!({
+        "data": [
+            {
+                "model": default_model,
+                "isDefault": true,
+                "defaultReasoningEffort": default_effort,
+                "supportedReasoningEfforts": [
+                    { "reasoningEffort": "minimal", "description": "Minimal effort" },
+                    { "reasoningEffort": "low", "description": "Low effort" },
+                    { "reasoningEffort": "medium", "description": "Medium effort" },
+                    { "reasoningEffort": "high", "description": "High effort" }
+                ]
+            }
+        ],
+        "nextCursor": Value::Null
+    }))
+}
+
+async fn respond_to_approval(
+    host: &dyn BackendHost,
+    state: &AppState,
+    response: &Map<String, Value>,
+) -> Result<bool, String> {
+    let request_id = response
+        .get("id")
+        .and_then(|value| {
+            value
+                .as_str()
+                .map(|s| s.to_string())
+                .or_else(|| value.as_i64().map(|v| v.to_string()))
+        })
+        .unwrap_or_default();
+    if request_id.trim().is_empty() {
+        return Ok(false);
+    }
+
+    let result = response.get("result").and_then(Value::as_object);
+    let decision = approvals::parse_approval_decision(result);
+
+    let pending = {
+        let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+        let pending = runtime.pending_approvals.remove(&request_id);
+        if let Some(pending) = &pending {
+            approvals::remember_approval_decision(&mut runtime, pending, &decision);
+        }
+        pending
+    };
+    let run_id = pending.map(|pending| pending.run_id).or_else(|| {
+        result
+            .and_then(|obj| obj.get("runId"))
+            .and_then(Value::as_str)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    });
+
+    let Some(run_id) = run_id else {
+        return Ok(false);
+    };
+
+    send_bridge_command(
+        host.app_handle()?,
+        state,
+        "approval.respond",
+        decision.to_command_data(&run_id, &request_id),
+    )
+    .await?;
+    Ok(true)
+}
+
+impl McpBackend for BridgeBackend {
+    fn list_threads<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(list_threads(self, call))
+    }
+
+    fn read_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(read_thread(call))
+    }
+
+    fn start_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(start_thread(call))
+    }
+
+    fn resume_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(resume_thread(call))
+    }
+
+    fn set_thread_archived<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        archived: bool,
+    ) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async move {
+            let thread_id = call
+                .str_param("threadId")
+                .ok_or_else(|| "thread/archive requires threadId".to_string())?;
+            self.set_archived(thread_id, archived)?;
+            Ok(json!({}))
+        })
+    }
+
+    fn delete_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async move {
+            let thread_id = call
+                .str_param("threadId")
+                .ok_or_else(|| "thread/delete requires threadId".to_string())?;
+            bridge_delete_json(call.state, &bridge_session_path(thread_id, ""))
+                .await
+                .map_err(|error| format!("thread/delete failed: {error}"))?;
+            self.set_archived(thread_id, false)?;
+            Ok(json!({}))
+        })
+    }
+
+    fn start_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(start_turn(call))
+    }
+
+    fn interrupt_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(interrupt_turn(call))
+    }
+
+    fn list_models<'a>(&'a self, _call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async { list_models() })
+    }
+
+    fn respond_to_approval<'a>(
+        &'a self,
+        host: &'a dyn BackendHost,
+        state: &'a AppState,
+        response: &'a Map<String, Value>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(respond_to_approval(host, state, response))
+    }
+
+    fn forward<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        method: &'a str,
+    ) -> BoxFuture<'a, Option<BackendResult>> {
+        Box::pin(async move {
+            match method {
+                "thread/turns/list" => Some(list_thread_turns(call).await),
+                "turn/revert" => Some(revert_turn(call).await),
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,325 @@
+// This backend keeps threads in the host's in-memory store and echoes turns back.
+// It lets the webview boot without any agent process, which is useful for UI work.
+
+use futures_util::future::BoxFuture;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use super::{BackendHost, BackendResult, McpBackend, McpCall};
+use crate::git::git_info_for_cwd;
+use crate::{
+    create_host_thread, ensure_thread, first_text_from_input, lock_or_err, normalize_root_string,
+    now_unix_seconds, preferred_workspace_cwd, promote_thread_in_order, set_host_thread_git_info,
+    AppState, HostThread, HostTurn, DEFAULT_MODEL, DEFAULT_REASONING_EFFORT,
+};
+
+pub(super) struct MemoryBackend;
+
+fn requested_cwd(call: &McpCall<'_>) -> Option<String> {
+    call.str_param("cwd").map(normalize_root_string)
+}
+
+fn stored_thread_cwd(state: &AppState, thread_id: &str) -> Option<String> {
+    let store = state.thread_store.lock().ok()?;
+    store
+        .threads
+        .get(thread_id)
+        .map(|thread| thread.cwd.clone())
+}
+
+fn list_threads(call: McpCall<'_>) -> BackendResult {
+    let archived = call
+        .params
+        .and_then(|p| p.get("archived"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let limit = call
+        .params
+        .and_then(|p| p.get("limit"))
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(200);
+    let offset = call
+        .str_param("cursor")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let store = lock_or_err(&call.state.thread_store, "thread_store")?;
+    let ordered_ids = store
+        .order
+        .iter()
+        .filter(|thread_id| {
+            store
+                .threads
+                .get(*thread_id)
+                .is_some_and(|thread| thread.archived == archived)
+        })
+        .collect::<Vec<_>>();
+
+    let data = ordered_ids
+        .iter()
+        .skip(offset)
+        .take(limit)
+        .filter_map(|thread_id| store.threads.get(*thread_id))
+        .map(HostThread::to_list_json)
+        .collect::<Vec<_>>();
+    let next_cursor = if offset + data.len() < ordered_ids.len() {
+        Value::String((offset + data.len()).to_string())
+    } else {
+        Value::Null
+    };
+    Ok(json!({ "data": data, "nextCursor": next_cursor }))
+}
+
+async fn read_thread(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/read requires threadId".to_string());
+    };
+    let include_turns = call
+        .params
+        .and_then(|p| p.get("includeTurns"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let fallback_cwd = preferred_workspace_cwd(state);
+    let cwd = stored_thread_cwd(state, thread_id).unwrap_or_else(|| fallback_cwd.clone());
+    let git_info = git_info_for_cwd(state, &cwd).await;
+
+    let mut store = lock_or_err(&state.thread_store, "thread_store")?;
+    let thread = ensure_thread(&mut store, thread_id, &fallback_cwd);
+    thread.archived = false;
+    thread.updated_at = now_unix_seconds();
+    set_host_thread_git_info(thread, git_info);
+    let thread_json = if include_turns {
+        thread.to_resume_json()
+    } else {
+        thread.to_list_json()
+    };
+    Ok(json!({ "thread": thread_json }))
+}
+
+async fn start_thread(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let cwd = requested_cwd(&call).unwrap_or_else(|| preferred_workspace_cwd(state));
+    let model = call.str_param("model").unwrap_or(DEFAULT_MODEL);
+    let reasoning_effort = call.str_param("effort").unwrap_or(DEFAULT_REASONING_EFFORT);
+    let preview = call
+        .params
+        .and_then(|p| p.get("input"))
+        .map(first_text_from_input)
+        .unwrap_or_default();
+    let git_info = git_info_for_cwd(state, &cwd).await;
+
+    let thread_json = {
+        let mut store = lock_or_err(&state.thread_store, "thread_store")?;
+        let mut thread = create_host_thread(None, cwd.clone(), preview);
+        set_host_thread_git_info(&mut thread, git_info);
+        let thread_id = thread.id.clone();
+        let response = thread.to_resume_json();
+        store.threads.insert(thread_id.clone(), thread);
+        promote_thread_in_order(&mut store, &thread_id);
+        response
+    };
+
+    Ok(json!({
+        "thread": thread_json,
+        "model": model,
+        "reasoningEffort": reasoning_effort,
+        "cwd": cwd,
+        "sessionMeta": {
+            "cwd": cwd
+        }
+    }))
+}
+
+async fn resume_thread(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/resume requires threadId".to_string());
+    };
+    let requested_cwd = requested_cwd(&call);
+    let fallback_cwd = requested_cwd
+        .clone()
+        .unwrap_or_else(|| preferred_workspace_cwd(state));
+    let model = call.str_param("model").unwrap_or(DEFAULT_MODEL);
+    let reasoning_effort = call.str_param("effort").unwrap_or(DEFAULT_REASONING_EFFORT);
+    let thread_cwd = requested_cwd
+        .clone()
+        .or_else(|| stored_thread_cwd(state, thread_id))
+        .unwrap_or_else(|| fallback_cwd.clone());
+    let git_info = git_info_for_cwd(state, &thread_cwd).await;
+
+    let (thread_json, cwd) = {
+        let mut store = lock_or_err(&state.thread_store, "thread_store")?;
+        let thread = ensure_thread(&mut store, thread_id, &fallback_cwd);
+        if let Some(cwd) = requested_cwd {
+            thread.cwd = cwd;
+        }
+        thread.updated_at = now_unix_seconds();
+        thread.archived = false;
+        set_host_thread_git_info(thread, git_info);
+        (thread.to_resume_json(), thread.cwd.clone())
+    };
+
+    Ok(json!({
+        "thread": thread_json,
+        "model": model,
+        "reasoningEffort": reasoning_effort,
+        "cwd": cwd,
+        "sessionMeta": {
+            "cwd": cwd
+        }
+    }))
+}
+
+fn set_thread_archived(call: McpCall<'_>, archived: bool) -> BackendResult {
+    if let Some(thread_id) = call.str_param("threadId") {
+        let mut store = lock_or_err(&call.state.thread_store, "thread_store")?;
+        if let Some(thread) = store.threads.get_mut(thread_id) {
+            thread.archived = archived;
+            thread.updated_at = now_unix_seconds();
+            if !archived {
+                promote_thread_in_order(&mut store, thread_id);
+            }
+        }
+    }
+    Ok(json!({}))
+}
+
+fn delete_thread(call: McpCall<'_>) -> BackendResult {
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("thread/delete requires threadId".to_string());
+    };
+    let mut store = lock_or_err(&call.state.thread_store, "thread_store")?;
+    if store.threads.remove(thread_id).is_none() {
+        return Err(format!("thread/delete failed: unknown thread {thread_id}"));
+    }
+    store.order.retain(|id| id != thread_id);
+    Ok(json!({}))
+}
+
+async fn start_turn(call: McpCall<'_>) -> BackendResult {
+    let state = call.state;
+    let Some(thread_id) = call.str_param("threadId") else {
+        return Err("turn/start requires threadId".to_string());
+    };
+    let input = call
+        .params
+        .and_then(|p| p.get("input"))
+        .cloned()
+        .unwrap_or_else(|| json!([]));
+    let cwd = requested_cwd(&call).unwrap_or_else(|| preferred_workspace_cwd(state));
+    let preview = first_text_from_input(&input);
+    let turn_id = Uuid::new_v4().to_string();
+    let git_info = git_info_for_cwd(state, &cwd).await;
+
+    {
+        let mut store = lock_or_err(&state.thread_store, "thread_store")?;
+        let thread = ensure_thread(&mut store, thread_id, &cwd);
+        thread.cwd = cwd.clone();
+        thread.updated_at = now_unix_seconds();
+        thread.archived = false;
+        set_host_thread_git_info(thread, git_info);
+        if !preview.is_empty() {
+            thread.preview = preview;
+        }
+
+        thread.turns.push(HostTurn {
+            id: turn_id.clone(),
+            status: "completed".to_string(),
+            error: None,
+            items: vec![
+                json!({
+                    "id": format!("user-{}", Uuid::new_v4()),
+                    "type": "userMessage",
+                    "content": input
+                }),
+                json!({
+                    "id": format!("assistant-{}", Uuid::new_v4()),
+                    "type": "agentMessage",
+                    "text": "Tauri 兼容层已接收这条消息。"
+                }),
+            ],
+        });
+    }
+
+    Ok(json!({
+        "threadId": thread_id,
+        "turn": {
+            "id": turn_id,
+            "status": "completed",
+            "error": null
+        }
+    }))
+}
+
+fn list_models() -> BackendResult {
+    Ok(json!({
+        "data": [
+            {
+                "model": DEFAULT_MODEL,
+                "isDefault": true,
+                "defaultReasoningEffort": DEFAULT_REASONING_EFFORT,
+                "supportedReasoningEfforts": [
+                    { "reasoningEffort": "minimal", "description": "Minimal effort" },
+                    { "reasoningEffort": "low", "description": "Low effort" },
+                    { "reasoningEffort": "medium", "description": "Medium effort" },
+                    { "reasoningEffort": "high", "description": "High effort" }
+                ]
+            }
+        ],
+        "nextCursor": null
+    }))
+}
+
+impl McpBackend for MemoryBackend {
+    fn list_threads<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async move { list_threads(call) })
+    }
+
+    fn read_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(read_thread(call))
+    }
+
+    fn start_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(start_thread(call))
+    }
+
+    fn resume_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(resume_thread(call))
+    }
+
+    fn set_thread_archived<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        archived: bool,
+    ) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async move { set_thread_archived(call, archived) })
+    }
+
+    fn delete_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async move { delete_thread(call) })
+    }
+
+    fn start_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(start_turn(call))
+    }
+
+    fn interrupt_turn<'a>(&'a self, _call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async { Ok(json!({ "success": true })) })
+    }
+
+    fn list_models<'a>(&'a self, _call: McpCall<'a>) -> BoxFuture<'a, BackendResult> {
+        Box::pin(async { list_models() })
+    }
+
+    // Echoed turns never ask for approval.
+    fn respond_to_approval<'a>(
+        &'a self,
+        _host: &'a dyn BackendHost,
+        _state: &'a AppState,
+        _response: &'a Map<String, Value>,
+    ) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async { Ok(false) })
+    }
+}
@@ -0,0 +1,331 @@
+// This module routes thread, turn, approval and model requests to the selected backend.
+// The bridge, the direct app-server and the in-memory store each implement `McpBackend`.
+
+mod app_server;
+mod bridge;
+mod memory;
+
+use futures_util::future::BoxFuture;
+use serde_json::{Map, Value};
+use std::env;
+use std::sync::Arc;
+use tauri::AppHandle;
+
+use crate::{
+    bridge_base_url_from_env, emit_message_to_app, find_bridge_server_executable,
+    mcp_error_payload, mcp_result_payload, purge_deleted_thread, read_cached_bridge_base_url,
+    AppState,
+};
+
+pub(crate) type BackendResult = Result<Value, String>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BackendMode {
+    Bridge,
+    AppServer,
+    Memory,
+}
+
+impl BackendMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            BackendMode::Bridge => "bridge",
+            BackendMode::AppServer => "app-server",
+            BackendMode::Memory => "memory",
+        }
+    }
+}
+
+// CODEX_TAURI_BACKEND picks the backend explicitly; otherwise a reachable or installed bridge wins.
+pub(crate) fn resolve_backend_mode() -> BackendMode {
+    let requested = env::var("CODEX_TAURI_BACKEND")
+        .ok()
+        .map(|value| value.trim().to_ascii_lowercase());
+    let mode = match requested.as_deref() {
+        Some("bridge") => BackendMode::Bridge,
+        Some("app-server" | "appserver" | "codex") => BackendMode::AppServer,
+        Some("memory" | "in-memory") => BackendMode::Memory,
+        _ => {
+            let has_bridge = bridge_base_url_from_env().is_some()
+                || read_cached_bridge_base_url().is_some()
+                || find_bridge_server_executable().is_some();
+            if !has_bridge && crate::app_server::codex_executable().is_some() {
+                BackendMode::AppServer
+            } else {
+                BackendMode::Bridge
+            }
+        }
+    };
+    println!("[tauri-host] using {} backend", mode.as_str());
+    mode
+}
+
+pub(crate) fn create_backend(mode: BackendMode) -> Arc<dyn McpBackend> {
+    match mode {
+        BackendMode::Bridge => Arc::new(bridge::BridgeBackend::default()),
+        BackendMode::AppServer => Arc::new(app_server::AppServerBackend),
+        BackendMode::Memory => Arc::new(memory::MemoryBackend),
+    }
+}
+
+// The part of the app a backend touches, so backends can run without a Tauri app in tests.
+pub(crate) trait BackendHost: Send + Sync {
+    // Broadcasts a message to every webview.
+    fn broadcast(&self, payload: Value) -> Result<(), String>;
+    // Backends that own a connection hand the app to its reader task.
+    fn app_handle(&self) -> Result<&AppHandle, String>;
+}
+
+impl BackendHost for AppHandle {
+    fn broadcast(&self, payload: Value) -> Result<(), String> {
+        emit_message_to_app(self, payload)
+    }
+
+    fn app_handle(&self) -> Result<&AppHandle, String> {
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct McpCall<'a> {
+    pub(crate) host: &'a dyn BackendHost,
+    pub(crate) state: &'a AppState,
+    pub(crate) params: Option<&'a Map<String, Value>>,
+}
+
+impl McpCall<'_> {
+    fn str_param(&self, key: &str) -> Option<&str> {
+        self.params
+            .and_then(|p| p.get(key))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+pub(crate) trait McpBackend: Send + Sync {
+    fn list_threads<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn read_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn start_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn resume_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn set_thread_archived<'a>(
+        &'a self,
+        call: McpCall<'a>,
+        archived: bool,
+    ) -> BoxFuture<'a, BackendResult>;
+    fn delete_thread<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn start_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn interrupt_turn<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+    fn list_models<'a>(&'a self, call: McpCall<'a>) -> BoxFuture<'a, BackendResult>;
+
+    // Returns Ok(false) when the response does not answer one of this backend's approval requests.
+    fn respond_to_approval<'a>(
+        &'a self,
+        host: &'a dyn BackendHost,
+        state: &'a AppState,
+        response: &'a Map<String, Value>,
+    ) -> BoxFuture<'a, Result<bool, String>>;
+
+    // Backends that speak the full protocol can answer methods outside this trait.
+    fn forward<'a>(
+        &'a self,
+        _call: McpCall<'a>,
+        _method: &'a str,
+    ) -> BoxFuture<'a, Option<BackendResult>> {
+        Box::pin(async { None })
+    }
+}
+
+// Returns None when neither the trait nor the backend handles the method; the host answers it.
+pub(crate) async fn route_mcp_request(
+    host: &dyn BackendHost,
+    state: &AppState,
+    id: &str,
+    method: &str,
+    params: Option<&Map<String, Value>>,
+) -> Option<Value> {
+    let backend = state.backend.clone();
+    let call = McpCall {
+        host,
+        state,
+        params,
+    };
+    let outcome = match method {
+        "thread/list" => backend.list_threads(call).await,
+        "thread/read" => backend.read_thread(call).await,
+        "thread/start" => backend.start_thread(call).await,
+        "thread/resume" => backend.resume_thread(call).await,
+        "thread/archive" => backend.set_thread_archived(call, true).await,
+        "thread/unarchive" => backend.set_thread_archived(call, false).await,
+        "thread/delete" => {
+            let outcome = backend.delete_thread(call).await;
+            if let (Ok(_), Some(thread_id)) = (&outcome, call.str_param("threadId")) {
+                purge_deleted_thread(host, state, thread_id);
+            }
+            outcome
+        }
+        "turn/start" => backend.start_turn(call).await,
+        "turn/interrupt" => backend.interrupt_turn(call).await,
+        "model/list" => backend.list_models(call).await,
+        _ => backend.forward(call, method).await?,
+    };
+
+    Some(match outcome {
+        Ok(result) => mcp_result_payload(id, result),
+        Err(error) => mcp_error_payload(id, error),
+    })
+}
+
+pub(crate) async fn route_mcp_response(
+    host: &dyn BackendHost,
+    state: &AppState,
+    response: &Map<String, Value>,
+) -> Result<bool, String> {
+    let backend = state.backend.clone();
+    backend.respond_to_approval(host, state, response).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GLOBAL_KEY_PINNED_THREAD_IDS;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct TestHost {
+        broadcasts: Mutex<Vec<Value>>,
+    }
+
+    impl BackendHost for TestHost {
+        fn broadcast(&self, payload: Value) -> Result<(), String> {
+            self.broadcasts.lock().unwrap().push(payload);
+            Ok(())
+        }
+
+        fn app_handle(&self) -> Result<&AppHandle, String> {
+            Err("no Tauri app in tests".to_string())
+        }
+    }
+
+    fn memory_state() -> AppState {
+        AppState::with_backend(create_backend(BackendMode::Memory))
+    }
+
+    fn call(host: &TestHost, state: &AppState, method: &str, params: Value) -> Option<Value> {
+        tauri::async_runtime::block_on(route_mcp_request(
+            host,
+            state,
+            "req-1",
+            method,
+            params.as_object(),
+        ))
+    }
+
+    fn result(host: &TestHost, state: &AppState, method: &str, params: Value) -> Value {
+        let response = call(host, state, method, params).expect("method is routed");
+        assert_eq!(response["message"]["id"], "req-1");
+        assert!(response["message"]["error"].is_null(), "{response}");
+        response["message"]["result"].clone()
+    }
+
+    fn cwd() -> String {
+        std::env::temp_dir().to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn memory_backend_routes_thread_and_turn_requests() {
+        let (host, state) = (TestHost::default(), memory_state());
+        let started = result(&host, &state, "thread/start", json!({ "cwd": cwd() }));
+        let thread_id = started["thread"]["id"].as_str().unwrap().to_string();
+
+        let listed = result(&host, &state, "thread/list", json!({}));
+        assert_eq!(listed["data"][0]["id"], thread_id.as_str());
+        assert!(listed["nextCursor"].is_null());
+
+        let turn = result(
+            &host,
+            &state,
+            "turn/start",
+            json!({
+                "threadId": thread_id,
+                "cwd": cwd(),
+                "input": [{ "type": "text", "text": "hello" }]
+            }),
+        );
+        assert_eq!(turn["turn"]["status"], "completed");
+
+        let read = result(
+            &host,
+            &state,
+            "thread/read",
+            json!({ "threadId": thread_id }),
+        );
+        assert_eq!(read["thread"]["turns"][0]["id"], turn["turn"]["id"]);
+
+        result(
+            &host,
+            &state,
+            "thread/archive",
+            json!({ "threadId": thread_id }),
+        );
+        let listed = result(&host, &state, "thread/list", json!({}));
+        assert_eq!(listed["data"], json!([]));
+        let archived = result(&host, &state, "thread/list", json!({ "archived": true }));
+        assert_eq!(archived["data"][0]["id"], thread_id.as_str());
+
+        let models = result(&host, &state, "model/list", json!({}));
+        assert_eq!(models["data"][0]["isDefault"], true);
+        assert!(host.broadcasts.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn memory_backend_reports_errors_and_leaves_other_methods_to_the_host() {
+        let (host, state) = (TestHost::default(), memory_state());
+        let response = call(&host, &state, "thread/read", json!({})).unwrap();
+        assert_eq!(
+            response["message"]["error"]["message"],
+            "thread/read requires threadId"
+        );
+        let response = call(
+            &host,
+            &state,
+            "thread/delete",
+            json!({ "threadId": "nope" }),
+        );
+        assert_eq!(
+            response.unwrap()["message"]["error"]["message"],
+            "thread/delete failed: unknown thread nope"
+        );
+        assert!(call(&host, &state, "config/read", json!({})).is_none());
+    }
+
+    #[test]
+    fn deleting_a_pinned_thread_unpins_it_in_the_webview() {
+        let (host, state) = (TestHost::default(), memory_state());
+        let started = result(&host, &state, "thread/start", json!({ "cwd": cwd() }));
+        let thread_id = started["thread"]["id"].as_str().unwrap().to_string();
+        state.persisted_atom_state.lock().unwrap().insert(
+            GLOBAL_KEY_PINNED_THREAD_IDS.to_string(),
+            json!([thread_id, "other"]),
+        );
+
+        result(
+            &host,
+            &state,
+            "thread/delete",
+            json!({ "threadId": thread_id }),
+        );
+        assert_eq!(
+            *host.broadcasts.lock().unwrap(),
+            vec![json!({
+                "type": "persisted-atom-updated",
+                "key": GLOBAL_KEY_PINNED_THREAD_IDS,
+                "value": ["other"],
+                "deleted": false
+            })]
+        );
+        let listed = result(&host, &state, "thread/list", json!({}));
+        assert_eq!(listed["data"], json!([]));
+    }
+}
@@ -0,0 +1,642 @@
+// This module defines typed models for the bridge WebSocket protocol.
+// Field names follow the camelCase JSON the .NET bridge server emits from WebSocketHub.
+// Only fields the host reads are modelled; serde ignores the rest of each payload.
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+
+pub(crate) const CHANNEL_BRIDGE_DIAGNOSTICS: &str = "codex_desktop:bridge-diagnostics";
+pub(crate) const BRIDGE_PROTOCOL_VERSION: u32 = 1;
+pub(crate) const MIN_BRIDGE_PROTOCOL_VERSION: u32 = 1;
+
+// Capabilities name optional features, not commands; callers check them before sending.
+pub(crate) const CAPABILITY_CHAT_IMAGES: &str = "chat.images";
+
+fn default_protocol_version() -> u32 {
+    BRIDGE_PROTOCOL_VERSION
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeEnvelope {
+    #[serde(default = "default_protocol_version")]
+    pub(crate) protocol_version: u32,
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) data: Value,
+}
+
+// Advertised by the health endpoint and `bridge.connected`; servers that predate negotiation send neither.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeServerInfo {
+    #[serde(default)]
+    pub(crate) protocol_version: Option<u32>,
+    #[serde(default)]
+    pub(crate) min_protocol_version: Option<u32>,
+    #[serde(default)]
+    pub(crate) capabilities: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) server_version: Option<String>,
+}
+
+impl BridgeServerInfo {
+    pub(crate) fn merge(&mut self, other: BridgeServerInfo) {
+        if other.protocol_version.is_some() {
+            self.protocol_version = other.protocol_version;
+        }
+        if other.min_protocol_version.is_some() {
+            self.min_protocol_version = other.min_protocol_version;
+        }
+        if other.capabilities.is_some() {
+            self.capabilities = other.capabilities;
+        }
+        if other.server_version.is_some() {
+            self.server_version = other.server_version;
+        }
+    }
+
+    // Picks the highest version both sides speak; a server without metadata speaks version 1 only.
+    pub(crate) fn negotiate(&self) -> Result<u32, String> {
+        let server_max = self.protocol_version.unwrap_or(1);
+        let server_min = self
+            .min_protocol_version
+            .unwrap_or(server_max)
+            .min(server_max);
+        let version = server_max.min(BRIDGE_PROTOCOL_VERSION);
+        if version < server_min.max(MIN_BRIDGE_PROTOCOL_VERSION) {
+            let server = self.server_version.as_deref().unwrap_or("unknown");
+            return Err(format!(
+                "Bridge server {server} speaks protocol {server_min}-{server_max}, but this app supports {MIN_BRIDGE_PROTOCOL_VERSION}-{BRIDGE_PROTOCOL_VERSION}. Update the app or the bridge server so their versions match."
+            ));
+        }
+        Ok(version)
+    }
+
+    // Servers that do not list capabilities expose the full version 1 feature set.
+    pub(crate) fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .as_ref()
+            .is_none_or(|capabilities| capabilities.iter().any(|c| c == capability))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeConnected {
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+    #[serde(flatten)]
+    pub(crate) server: BridgeServerInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionCreated {
+    pub(crate) run_id: String,
+    pub(crate) session_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunStarted {
+    pub(crate) run_id: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnStarted {
+    pub(crate) run_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatMessageDelta {
+    pub(crate) run_id: String,
+    pub(crate) item_id: String,
+    #[serde(default)]
+    pub(crate) delta: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatMessage {
+    #[serde(default)]
+    pub(crate) run_id: Option<String>,
+    #[serde(default)]
+    pub(crate) role: String,
+    #[serde(default)]
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunReasoning {
+    pub(crate) run_id: String,
+    pub(crate) item_id: String,
+    #[serde(default, alias = "textDelta", alias = "delta")]
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunCommand {
+    pub(crate) run_id: String,
+    pub(crate) item_id: String,
+    #[serde(default)]
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) status: Option<String>,
+    #[serde(default)]
+    pub(crate) exit_code: Option<i32>,
+    #[serde(default)]
+    pub(crate) output: Option<String>,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunCommandOutputDelta {
+    pub(crate) run_id: String,
+    pub(crate) item_id: String,
+    #[serde(default)]
+    pub(crate) delta: String,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlanStep {
+    pub(crate) step: String,
+    pub(crate) status: String,
+}
+
+impl PlanStep {
+    // The app-server speaks camelCase statuses; older runners wrote snake_case.
+    pub(crate) fn normalized_status(&self) -> &str {
+        match self.status.trim() {
+            "in_progress" | "inProgress" | "in-progress" => "inProgress",
+            "completed" | "done" => "completed",
+            _ => "pending",
+        }
+    }
+}
+
+// Body of `GET api/v1/sessions/{id}/plan`, the latest plan the bridge cached for a session.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnPlanSnapshot {
+    #[serde(default)]
+    pub(crate) explanation: Option<String>,
+    #[serde(default)]
+    pub(crate) plan: Vec<PlanStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunPlanUpdated {
+    pub(crate) run_id: String,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) explanation: Option<String>,
+    #[serde(default)]
+    pub(crate) plan: Vec<PlanStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffFile {
+    pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) diff: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffUpdated {
+    pub(crate) run_id: String,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) files: Vec<DiffFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffSummary {
+    pub(crate) run_id: String,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApprovalRequested {
+    pub(crate) request_id: String,
+    #[serde(default)]
+    pub(crate) run_id: Option<String>,
+    #[serde(default)]
+    pub(crate) kind: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) item_id: Option<String>,
+    #[serde(default)]
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) cwd: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApprovalResponded {
+    pub(crate) request_id: String,
+    #[serde(default)]
+    pub(crate) decision: Option<String>,
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunFinished {
+    pub(crate) run_id: String,
+    #[serde(default)]
+    pub(crate) message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunCancelRequested {
+    #[serde(default)]
+    pub(crate) run_id: Option<String>,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+    // The paired device behind `client_id`; null for loopback clients and older servers.
+    #[serde(default)]
+    pub(crate) device_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RunRejected {
+    #[serde(default)]
+    pub(crate) reason: Option<String>,
+    // The rejected command's name; servers before this field omit it.
+    #[serde(default)]
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    #[serde(default)]
+    pub(crate) thread_id: Option<String>,
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DevicePresenceUpdated {
+    pub(crate) device_id: String,
+    #[serde(default)]
+    pub(crate) online: bool,
+    #[serde(default)]
+    pub(crate) last_seen_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DevicePairingRequested {
+    pub(crate) request_id: String,
+    #[serde(default)]
+    pub(crate) device_name: Option<String>,
+    #[serde(default)]
+    pub(crate) platform: Option<String>,
+    #[serde(default)]
+    pub(crate) device_model: Option<String>,
+    #[serde(default)]
+    pub(crate) app_version: Option<String>,
+    #[serde(default)]
+    pub(crate) client_ip: Option<String>,
+    #[serde(default)]
+    pub(crate) expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeError {
+    #[serde(default)]
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) client_id: Option<String>,
+}
+
+// Declares `BridgeEvent` and `KNOWN_EVENT_NAMES` from one list so the two cannot drift.
+macro_rules! bridge_events {
+    ($($name:literal => $variant:ident($payload:ty),)*) => {
+        #[derive(Debug, Deserialize)]
+        #[serde(tag = "name", content = "data")]
+        pub(crate) enum BridgeEvent {
+            $(
+                #[serde(rename = $name)]
+                $variant($payload),
+            )*
+        }
+
+        const KNOWN_EVENT_NAMES: &[&str] = &[$($name),*];
+    };
+}
+
+// The active-run snapshot only matters to clients that reattach to runs; the host ignores it.
+bridge_events! {
+    "bridge.connected" => BridgeConnected(BridgeConnected),
+    "run.active.snapshot" => RunActiveSnapshot(IgnoredAny),
+    "session.created" => SessionCreated(SessionCreated),
+    "run.started" => RunStarted(RunStarted),
+    "turn.started" => TurnStarted(TurnStarted),
+    "chat.message.delta" => ChatMessageDelta(ChatMessageDelta),
+    "chat.message" => ChatMessage(ChatMessage),
+    "run.reasoning.delta" => RunReasoningDelta(RunReasoning),
+    "run.reasoning" => RunReasoning(RunReasoning),
+    "run.command" => RunCommand(RunCommand),
+    "run.command.outputDelta" => RunCommandOutputDelta(RunCommandOutputDelta),
+    "run.plan.updated" => RunPlanUpdated(RunPlanUpdated),
+    "diff.updated" => DiffUpdated(DiffUpdated),
+    "diff.summary" => DiffSummary(DiffSummary),
+    "approval.requested" => ApprovalRequested(ApprovalRequested),
+    "approval.responded" => ApprovalResponded(ApprovalResponded),
+    "run.completed" => RunCompleted(RunFinished),
+    "run.failed" => RunFailed(RunFinished),
+    "run.canceled" => RunCanceled(RunFinished),
+    "run.cancel.requested" => RunCancelRequested(RunCancelRequested),
+    "run.rejected" => RunRejected(RunRejected),
+    "device.presence.updated" => DevicePresenceUpdated(DevicePresenceUpdated),
+    "device.pairing.requested" => DevicePairingRequested(DevicePairingRequested),
+    "bridge.error" => BridgeError(BridgeError),
+}
+
+#[derive(Debug)]
+pub(crate) enum BridgeDecodeError {
+    InvalidEnvelope(String),
+    UnsupportedVersion { name: String, version: u32 },
+    UnknownEvent(String),
+    MalformedEvent { name: String, error: String },
+}
+
+impl BridgeDecodeError {
+    fn kind(&self) -> &'static str {
+        match self {
+            BridgeDecodeError::InvalidEnvelope(_) => "invalid-envelope",
+            BridgeDecodeError::UnsupportedVersion { .. } => "unsupported-protocol-version",
+            BridgeDecodeError::UnknownEvent(_) => "unknown-event",
+            BridgeDecodeError::MalformedEvent { .. } => "malformed-event",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            BridgeDecodeError::InvalidEnvelope(error) => format!("Invalid bridge envelope: {error}"),
+            BridgeDecodeError::UnsupportedVersion { name, version } => format!(
+                "Bridge event {name} uses protocolVersion {version}; this host speaks {MIN_BRIDGE_PROTOCOL_VERSION}-{BRIDGE_PROTOCOL_VERSION}"
+            ),
+            BridgeDecodeError::UnknownEvent(name) => format!("Unknown bridge event: {name}"),
+            BridgeDecodeError::MalformedEvent { name, error } => {
+                format!("Malformed bridge event {name}: {error}")
+            }
+        }
+    }
+}
+
+pub(crate) struct DecodedEvent {
+    pub(crate) event: BridgeEvent,
+    pub(crate) data: Value,
+}
+
+// Returns Ok(None) for non-event envelopes such as command responses.
+pub(crate) fn decode_bridge_event(raw: Value) -> Result<Option<DecodedEvent>, BridgeDecodeError> {
+    let envelope = serde_json::from_value::<BridgeEnvelope>(raw)
+        .map_err(|e| BridgeDecodeError::InvalidEnvelope(e.to_string()))?;
+    if envelope.kind != "event" {
+        return Ok(None);
+    }
+    if !(MIN_BRIDGE_PROTOCOL_VERSION..=BRIDGE_PROTOCOL_VERSION).contains(&envelope.protocol_version)
+    {
+        return Err(BridgeDecodeError::UnsupportedVersion {
+            name: envelope.name,
+            version: envelope.protocol_version,
+        });
+    }
+    if !KNOWN_EVENT_NAMES.contains(&envelope.name.as_str()) {
+        return Err(BridgeDecodeError::UnknownEvent(envelope.name));
+    }
+
+    let data = envelope.data;
+    let event = serde_json::from_value::<BridgeEvent>(json!({
+        "name": envelope.name,
+        "data": data.clone()
+    }))
+    .map_err(|e| BridgeDecodeError::MalformedEvent {
+        name: envelope.name.clone(),
+        error: e.to_string(),
+    })?;
+    Ok(Some(DecodedEvent { event, data }))
+}
+
+pub(crate) fn emit_bridge_diagnostic(app: &AppHandle, kind: &str, message: &str, raw: Value) {
+    eprintln!("[tauri-host] {message}");
+    let payload = json!({
+        "kind": kind,
+        "message": message,
+        "raw": raw
+    });
+    if let Err(e) = app.emit(CHANNEL_BRIDGE_DIAGNOSTICS, payload) {
+        eprintln!("[tauri-host] emit bridge diagnostic failed: {e}");
+    }
+}
+
+pub(crate) fn report_bridge_diagnostic(app: &AppHandle, error: &BridgeDecodeError, raw: &str) {
+    emit_bridge_diagnostic(app, error.kind(), &error.message(), Value::from(raw));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Envelopes as WebSocketHub serializes them: web defaults, nulls kept, `ts` always present.
+    fn decode(raw: &str) -> Result<Option<DecodedEvent>, BridgeDecodeError> {
+        decode_bridge_event(serde_json::from_str(raw).expect("test payload is JSON"))
+    }
+
+    fn decode_event(raw: &str) -> BridgeEvent {
+        decode(raw)
+            .expect("payload decodes")
+            .expect("payload is an event")
+            .event
+    }
+
+    #[test]
+    fn every_known_name_maps_to_a_variant() {
+        for name in KNOWN_EVENT_NAMES {
+            let result = serde_json::from_value::<BridgeEvent>(json!({ "name": name, "data": {} }));
+            if let Err(error) = result {
+                assert!(
+                    !error.to_string().contains("unknown variant"),
+                    "{name} has no BridgeEvent variant: {error}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_bridge_connected_with_capabilities() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"bridge.connected","id":null,"ts":"2026-01-02T03:04:05.678+00:00","data":{"clientId":"c1","protocolVersion":1,"minProtocolVersion":1,"capabilities":["chat.images"],"serverVersion":"1.2.0"}}"#,
+        );
+        let BridgeEvent::BridgeConnected(connected) = event else {
+            panic!("expected bridge.connected, got {event:?}");
+        };
+        assert_eq!(connected.client_id.as_deref(), Some("c1"));
+        assert!(connected.server.supports(CAPABILITY_CHAT_IMAGES));
+        assert_eq!(connected.server.negotiate(), Ok(1));
+    }
+
+    #[test]
+    fn decodes_run_active_snapshot_without_reading_it() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"run.active.snapshot","id":null,"ts":"2026-01-02T03:04:05Z","data":{"activeRuns":[{"sessionId":"s1","runId":"r1"}]}}"#,
+        );
+        assert!(matches!(event, BridgeEvent::RunActiveSnapshot(_)));
+    }
+
+    #[test]
+    fn decodes_run_rejected_with_and_without_command() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"run.rejected","id":null,"ts":"2026-01-02T03:04:05Z","data":{"clientId":"c1","command":"run.cancel","sessionId":"s1","reason":"nothing to cancel"}}"#,
+        );
+        let BridgeEvent::RunRejected(rejected) = event else {
+            panic!("expected run.rejected, got {event:?}");
+        };
+        assert_eq!(rejected.client_id.as_deref(), Some("c1"));
+        assert_eq!(rejected.command.as_deref(), Some("run.cancel"));
+        assert_eq!(rejected.session_id.as_deref(), Some("s1"));
+
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"run.rejected","id":null,"ts":"2026-01-02T03:04:05Z","data":{"reason":"missing prompt"}}"#,
+        );
+        let BridgeEvent::RunRejected(rejected) = event else {
+            panic!("expected run.rejected, got {event:?}");
+        };
+        assert_eq!(rejected.command, None);
+        assert_eq!(rejected.client_id, None);
+    }
+
+    #[test]
+    fn decodes_reasoning_delta_alias() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"run.reasoning.delta","id":null,"ts":"2026-01-02T03:04:05Z","data":{"runId":"r1","itemId":"p1","textDelta":"Thinking","sessionId":"s1"}}"#,
+        );
+        let BridgeEvent::RunReasoningDelta(reasoning) = event else {
+            panic!("expected run.reasoning.delta, got {event:?}");
+        };
+        assert_eq!(reasoning.text, "Thinking");
+        assert_eq!(reasoning.session_id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn decodes_approval_requested_with_null_fields() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"approval.requested","id":null,"ts":"2026-01-02T03:04:05Z","data":{"runId":"r1","requestId":"7","kind":"commandExecution","threadId":"t1","turnId":"u1","itemId":"i1","reason":null,"proposedExecpolicyAmendment":null,"grantRoot":null,"command":"cargo test","cwd":"/repo"}}"#,
+        );
+        let BridgeEvent::ApprovalRequested(approval) = event else {
+            panic!("expected approval.requested, got {event:?}");
+        };
+        assert_eq!(approval.request_id, "7");
+        assert_eq!(approval.command.as_deref(), Some("cargo test"));
+        assert_eq!(approval.cwd.as_deref(), Some("/repo"));
+    }
+
+    #[test]
+    fn decodes_diff_updated_files() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"diff.updated","id":null,"ts":"2026-01-02T03:04:05Z","data":{"runId":"r1","threadId":"t1","files":[{"path":"src/a.rs","diff":"@@ -1 +1 @@\n-a\n+b\n","added":1,"removed":1}]}}"#,
+        );
+        let BridgeEvent::DiffUpdated(diff) = event else {
+            panic!("expected diff.updated, got {event:?}");
+        };
+        assert_eq!(diff.files.len(), 1);
+        assert_eq!(diff.files[0].path, "src/a.rs");
+    }
+
+    #[test]
+    fn decodes_run_failed_message() {
+        let event = decode_event(
+            r#"{"protocolVersion":1,"type":"event","name":"run.failed","id":null,"ts":"2026-01-02T03:04:05Z","data":{"runId":"r1","sessionId":"s1","message":"boom"}}"#,
+        );
+        let BridgeEvent::RunFailed(finished) = event else {
+            panic!("expected run.failed, got {event:?}");
+        };
+        assert_eq!(finished.message.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn skips_command_responses() {
+        let decoded = decode(
+            r#"{"protocolVersion":1,"type":"response","name":"chat.send","id":"req-1","ts":"2026-01-02T03:04:05Z","data":{"runId":"r1"}}"#,
+        )
+        .expect("response envelope is valid");
+        assert!(decoded.is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_events() {
+        let unknown = decode(r#"{"type":"event","name":"run.mystery","data":{}}"#);
+        assert!(
+            matches!(unknown, Err(BridgeDecodeError::UnknownEvent(name)) if name == "run.mystery")
+        );
+
+        let malformed = decode(r#"{"type":"event","name":"run.command","data":{"itemId":"i1"}}"#);
+        assert!(matches!(
+            malformed,
+            Err(BridgeDecodeError::MalformedEvent { .. })
+        ));
+
+        let future =
+            decode(r#"{"protocolVersion":99,"type":"event","name":"run.command","data":{}}"#);
+        assert!(matches!(
+            future,
+            Err(BridgeDecodeError::UnsupportedVersion { version: 99, .. })
+        ));
+    }
+}
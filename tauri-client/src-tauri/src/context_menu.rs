@@ -0,0 +1,298 @@
+// This module turns Electron-style context menu templates into native Tauri menus.
+// The webview sends the same item tree it used with Electron's Menu.buildFromTemplate.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::menu::{
+    CheckMenuItemBuilder, IsMenuItem, Menu, MenuEvent, MenuItemBuilder, PredefinedMenuItem, Submenu,
+};
+use tauri::{AppHandle, Manager, Window, Wry};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{lock_or_err, AppState};
+
+const CONTEXT_MENU_ID_PREFIX: &str = "ctx:";
+// Windows and macOS block inside popup until the menu closes; the click event may trail it.
+#[cfg(not(target_os = "linux"))]
+const CONTEXT_MENU_SELECTION_GRACE: std::time::Duration = std::time::Duration::from_millis(150);
+// GTK reports no dismissal, so a menu closed without a choice resolves as dismissed after this.
+#[cfg(target_os = "linux")]
+const CONTEXT_MENU_DISMISS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContextMenuItem {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    role: Option<String>,
+    #[serde(default)]
+    enabled: Option<bool>,
+    #[serde(default)]
+    visible: Option<bool>,
+    #[serde(default)]
+    checked: Option<bool>,
+    #[serde(default)]
+    accelerator: Option<String>,
+    #[serde(default)]
+    submenu: Option<Vec<ContextMenuItem>>,
+}
+
+impl ContextMenuItem {
+    fn item_id(&self) -> Option<String> {
+        match self.id.as_ref()? {
+            Value::String(id) if !id.trim().is_empty() => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> &str {
+        if let Some(kind) = self.kind.as_deref() {
+            return kind;
+        }
+        if self.submenu.is_some() {
+            "submenu"
+        } else {
+            "normal"
+        }
+    }
+
+    fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or_default()
+    }
+}
+
+struct PendingContextMenu {
+    token: String,
+    ids: Vec<String>,
+    sender: oneshot::Sender<Option<String>>,
+}
+
+#[derive(Default)]
+pub(crate) struct ContextMenuState {
+    pending: Mutex<Option<PendingContextMenu>>,
+}
+
+fn parse_context_menu_items(items: &Value) -> Result<Vec<ContextMenuItem>, String> {
+    let items = match items {
+        Value::Null => return Ok(Vec::new()),
+        Value::Object(obj) => obj.get("items").cloned().unwrap_or(Value::Null),
+        other => other.clone(),
+    };
+    if items.is_null() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_value::<Vec<ContextMenuItem>>(items)
+        .map_err(|e| format!("Parse context menu items failed: {e}"))
+}
+
+fn native_item_id(token: &str, index: usize) -> String {
+    format!("{CONTEXT_MENU_ID_PREFIX}{token}:{index}")
+}
+
+fn build_role_item(
+    app: &AppHandle,
+    role: &str,
+    label: Option<&str>,
+) -> Option<tauri::Result<PredefinedMenuItem<Wry>>> {
+    let label = label.filter(|text| !text.trim().is_empty());
+    let item = match role.to_ascii_lowercase().as_str() {
+        "copy" => PredefinedMenuItem::copy(app, label),
+        "cut" => PredefinedMenuItem::cut(app, label),
+        "paste" => PredefinedMenuItem::paste(app, label),
+        "selectall" => PredefinedMenuItem::select_all(app, label),
+        "undo" => PredefinedMenuItem::undo(app, label),
+        "redo" => PredefinedMenuItem::redo(app, label),
+        _ => return None,
+    };
+    Some(item)
+}
+
+fn build_menu_items(
+    app: &AppHandle,
+    token: &str,
+    items: &[ContextMenuItem],
+    ids: &mut Vec<String>,
+) -> Result<Vec<Box<dyn IsMenuItem<Wry>>>, String> {
+    let mut built: Vec<Box<dyn IsMenuItem<Wry>>> = Vec::new();
+
+    for item in items {
+        if item.visible == Some(false) {
+            continue;
+        }
+        let enabled = item.enabled.unwrap_or(true);
+
+        if item.kind() == "separator" {
+            let separator = PredefinedMenuItem::separator(app)
+                .map_err(|e| format!("Build context menu separator failed: {e}"))?;
+            built.push(Box::new(separator));
+            continue;
+        }
+
+        if let Some(role) = item.role.as_deref() {
+            if let Some(role_item) = build_role_item(app, role, item.label.as_deref()) {
+                let role_item =
+                    role_item.map_err(|e| format!("Build context menu role {role} failed: {e}"))?;
+                built.push(Box::new(role_item));
+                continue;
+            }
+        }
+
+        let native_id = native_item_id(token, ids.len());
+        ids.push(item.item_id().unwrap_or_else(|| native_id.clone()));
+
+        match item.kind() {
+            "submenu" => {
+                let children =
+                    build_menu_items(app, token, item.submenu.as_deref().unwrap_or_default(), ids)?;
+                let child_refs = children
+                    .iter()
+                    .map(|child| child.as_ref())
+                    .collect::<Vec<_>>();
+                let submenu =
+                    Submenu::with_id_and_items(app, native_id, item.label(), enabled, &child_refs)
+                        .map_err(|e| format!("Build context submenu failed: {e}"))?;
+                built.push(Box::new(submenu));
+            }
+            "checkbox" | "radio" => {
+                let build = |accelerator: Option<&str>| {
+                    let mut builder =
+                        CheckMenuItemBuilder::with_id(native_id.clone(), item.label())
+                            .enabled(enabled)
+                            .checked(item.checked.unwrap_or(false));
+                    if let Some(accelerator) = accelerator {
+                        builder = builder.accelerator(accelerator);
+                    }
+                    builder.build(app)
+                };
+                // Electron accepts accelerators Tauri cannot parse; keep the item without it.
+                let check = build(item.accelerator.as_deref())
+                    .or_else(|_| build(None))
+                    .map_err(|e| format!("Build context menu checkbox failed: {e}"))?;
+                built.push(Box::new(check));
+            }
+            _ => {
+                let build = |accelerator: Option<&str>| {
+                    let mut builder =
+                        MenuItemBuilder::with_id(native_id.clone(), item.label()).enabled(enabled);
+                    if let Some(accelerator) = accelerator {
+                        builder = builder.accelerator(accelerator);
+                    }
+                    builder.build(app)
+                };
+                let normal = build(item.accelerator.as_deref())
+                    .or_else(|_| build(None))
+                    .map_err(|e| format!("Build context menu item failed: {e}"))?;
+                built.push(Box::new(normal));
+            }
+        }
+    }
+
+    Ok(built)
+}
+
+fn resolve_selected_id(token: &str, ids: &[String], native_id: &str) -> Option<String> {
+    let rest = native_id.strip_prefix(CONTEXT_MENU_ID_PREFIX)?;
+    let (menu_token, index) = rest.rsplit_once(':')?;
+    if menu_token != token {
+        return None;
+    }
+    let index = index.parse::<usize>().ok()?;
+    ids.get(index).cloned()
+}
+
+pub(crate) fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let native_id = event.id().as_ref();
+    if !native_id.starts_with(CONTEXT_MENU_ID_PREFIX) {
+        return;
+    }
+
+    let state = app.state::<AppState>();
+    let Ok(mut pending) = state.context_menu.pending.lock() else {
+        return;
+    };
+    let Some(current) = pending.take() else {
+        return;
+    };
+    match resolve_selected_id(&current.token, &current.ids, native_id) {
+        Some(selected) => {
+            let _ = current.sender.send(Some(selected));
+        }
+        None => {
+            *pending = Some(current);
+        }
+    }
+}
+
+fn clear_pending_menu(state: &AppState, token: &str) {
+    if let Ok(mut pending) = state.context_menu.pending.lock() {
+        if pending
+            .as_ref()
+            .is_some_and(|current| current.token == token)
+        {
+            pending.take();
+        }
+    }
+}
+
+pub(crate) async fn show_context_menu(
+    window: &Window,
+    state: &AppState,
+    items: &Value,
+) -> Result<Option<String>, String> {
+    let items = parse_context_menu_items(items)?;
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    let app = window.app_handle();
+    let token = Uuid::new_v4().simple().to_string();
+    let mut ids = Vec::new();
+    let menu = {
+        let built = build_menu_items(app, &token, &items, &mut ids)?;
+        let item_refs = built.iter().map(|item| item.as_ref()).collect::<Vec<_>>();
+        Menu::with_items(app, &item_refs).map_err(|e| format!("Build context menu failed: {e}"))?
+    };
+
+    let (sender, receiver) = oneshot::channel::<Option<String>>();
+    {
+        let mut pending = lock_or_err(&state.context_menu.pending, "context_menu")?;
+        let next = PendingContextMenu {
+            token: token.clone(),
+            ids,
+            sender,
+        };
+        // Opening a new menu implicitly dismisses the previous one.
+        if let Some(previous) = pending.replace(next) {
+            let _ = previous.sender.send(None);
+        }
+    }
+
+    if let Err(error) = window.popup_menu(&menu) {
+        clear_pending_menu(state, &token);
+        return Err(format!("Show context menu failed: {error}"));
+    }
+
+    // GTK returns from popup immediately, so wait for a selection, the next menu or the timeout.
+    #[cfg(target_os = "linux")]
+    let wait = CONTEXT_MENU_DISMISS_TIMEOUT;
+    #[cfg(not(target_os = "linux"))]
+    let wait = CONTEXT_MENU_SELECTION_GRACE;
+
+    let selected = match tokio::time::timeout(wait, receiver).await {
+        Ok(result) => result.ok().flatten(),
+        Err(_) => {
+            clear_pending_menu(state, &token);
+            None
+        }
+    };
+
+    Ok(selected)
+}
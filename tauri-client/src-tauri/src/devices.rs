@@ -0,0 +1,326 @@
+// This module manages devices paired with the bridge server.
+// The desktop host is the pairing authority, so incoming pairing requests are confirmed natively.
+
+use rfd::{AsyncMessageDialog, MessageButtons, MessageDialogResult, MessageLevel};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager};
+
+use crate::bridge_protocol::DevicePairingRequested;
+use crate::{
+    bridge_delete_json, bridge_get_json, bridge_post_json, encode_path_segment, lock_or_err,
+    AppState,
+};
+
+const DEVICES_PATH: &str = "api/v1/connections/devices";
+
+// Presence events are newer than the server's device store snapshot, so they win.
+fn merge_live_presence(state: &AppState, devices: &mut [Value]) -> Result<(), String> {
+    let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    for device in devices.iter_mut() {
+        let Some(device) = device.as_object_mut() else {
+            continue;
+        };
+        let Some(presence) = device
+            .get("deviceId")
+            .and_then(Value::as_str)
+            .and_then(|device_id| runtime.device_presence.get(device_id))
+        else {
+            continue;
+        };
+        device.insert("online".to_string(), Value::Bool(presence.online));
+        if let Some(last_seen_at) = &presence.last_seen_at {
+            device.insert(
+                "lastSeenAt".to_string(),
+                Value::String(last_seen_at.clone()),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn presence_only_devices(state: &AppState) -> Result<Vec<Value>, String> {
+    let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    let mut devices = runtime
+        .device_presence
+        .iter()
+        .map(|(device_id, presence)| {
+            json!({
+                "deviceId": device_id,
+                "online": presence.online,
+                "lastSeenAt": presence.last_seen_at
+            })
+        })
+        .collect::<Vec<_>>();
+    devices.sort_by(|a, b| a["deviceId"].as_str().cmp(&b["deviceId"].as_str()));
+    Ok(devices)
+}
+
+fn remember_device_names(state: &AppState, devices: &[Value]) -> Result<(), String> {
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    for device in devices {
+        if let (Some(device_id), Some(name)) = (
+            device.get("deviceId").and_then(Value::as_str),
+            device.get("name").and_then(Value::as_str),
+        ) {
+            runtime
+                .device_names
+                .insert(device_id.to_string(), name.to_string());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn list_devices(state: &AppState) -> Result<Value, String> {
+    let mut devices = match bridge_get_json(state, DEVICES_PATH).await? {
+        Value::Array(devices) => devices,
+        other => return Err(format!("Unexpected devices payload: {other}")),
+    };
+    remember_device_names(state, &devices)?;
+    merge_live_presence(state, &mut devices)?;
+    Ok(json!({ "devices": devices }))
+}
+
+// Falls back to the devices seen over the websocket when the management API is unreachable.
+pub(crate) async fn list_devices_or_presence(state: &AppState) -> Result<Value, String> {
+    match list_devices(state).await {
+        Ok(value) => Ok(value),
+        Err(error) => Ok(json!({
+            "devices": presence_only_devices(state)?,
+            "error": error
+        })),
+    }
+}
+
+pub(crate) async fn revoke_device(
+    state: &AppState,
+    params: Option<&Map<String, Value>>,
+) -> Result<Value, String> {
+    let device_id = params
+        .and_then(|p| p.get("deviceId").or_else(|| p.get("id")))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| "revoke-device requires deviceId".to_string())?;
+
+    let path = format!("{DEVICES_PATH}/{}", encode_path_segment(device_id));
+    bridge_delete_json(state, &path).await?;
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    runtime.device_presence.remove(device_id);
+    Ok(json!({ "success": true, "deviceId": device_id }))
+}
+
+// Loads device names for cancel attribution; an unreachable management API only costs the names.
+pub(crate) fn refresh_device_names(app: &AppHandle) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let state_handle = app.state::<AppState>();
+        if let Err(error) = list_devices(state_handle.inner()).await {
+            eprintln!("[tauri-host] device list refresh failed: {error}");
+        }
+    });
+}
+
+// Names who cancelled a run: the paired device's name, else its id, else the bare client id.
+pub(crate) fn cancelled_by_error(
+    device_names: &HashMap<String, String>,
+    client_id: Option<&str>,
+    device_id: Option<&str>,
+) -> Value {
+    let device_name = device_id.and_then(|device_id| device_names.get(device_id));
+    let label = match (device_name, device_id, client_id) {
+        (Some(name), _, _) => name.clone(),
+        (None, Some(device_id), _) => format!("device {device_id}"),
+        (None, None, Some(client_id)) => format!("another client ({client_id})"),
+        (None, None, None) => "another device".to_string(),
+    };
+    json!({
+        "message": format!("Cancelled from {label}"),
+        "cancelledBy": {
+            "clientId": client_id,
+            "deviceId": device_id,
+            "deviceName": device_name
+        }
+    })
+}
+
+fn pairing_description(request: &DevicePairingRequested) -> String {
+    let name = request.device_name.as_deref().unwrap_or("Unknown device");
+    let mut lines = vec![format!("{name} wants to connect to this computer.")];
+    let model = [request.platform.as_deref(), request.device_model.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !model.is_empty() {
+        lines.push(format!("Device: {model}"));
+    }
+    if let Some(app_version) = &request.app_version {
+        lines.push(format!("App version: {app_version}"));
+    }
+    if let Some(client_ip) = &request.client_ip {
+        lines.push(format!("Address: {client_ip}"));
+    }
+    if let Some(expires_at) = &request.expires_at {
+        lines.push(format!("Request expires at {expires_at}"));
+    }
+    lines.push("Allow this device to pair?".to_string());
+    lines.join("\n")
+}
+
+// Runs off the websocket reader so an unanswered prompt never stalls bridge events.
+pub(crate) fn prompt_pairing_request(app: &AppHandle, request: DevicePairingRequested) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let answer = AsyncMessageDialog::new()
+            .set_level(MessageLevel::Warning)
+            .set_title("Pair new device")
+            .set_description(pairing_description(&request))
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            .await;
+        let decision = if answer == MessageDialogResult::Yes {
+            "approve"
+        } else {
+            "decline"
+        };
+
+        let state_handle = app.state::<AppState>();
+        let path = format!(
+            "api/v1/connections/pairings/{}/respond",
+            encode_path_segment(&request.request_id)
+        );
+        if let Err(error) =
+            bridge_post_json(state_handle.inner(), &path, json!({ "decision": decision })).await
+        {
+            eprintln!(
+                "[tauri-host] pairing response for {} failed: {error}",
+                request.request_id
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{create_backend, BackendMode};
+    use crate::DevicePresence;
+
+    fn pairing_request(request_id: &str) -> DevicePairingRequested {
+        DevicePairingRequested {
+            request_id: request_id.to_string(),
+            device_name: None,
+            platform: None,
+            device_model: None,
+            app_version: None,
+            client_ip: None,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn pairing_description_lists_only_known_details() {
+        let bare = pairing_request("req-1");
+        assert_eq!(
+            pairing_description(&bare),
+            "Unknown device wants to connect to this computer.
+Allow this device to pair?"
+        );
+
+        let full = DevicePairingRequested {
+            device_name: Some("Pixel 8".to_string()),
+            platform: Some("Android".to_string()),
+            device_model: Some("GKWS6".to_string()),
+            app_version: Some("1.4.0".to_string()),
+            client_ip: Some("192.168.1.20".to_string()),
+            expires_at: Some("2026-10-18T10:05:00Z".to_string()),
+            ..pairing_request("req-2")
+        };
+        assert_eq!(
+            pairing_description(&full),
+            "Pixel 8 wants to connect to this computer.\n\
+             Device: Android GKWS6\n\
+             App version: 1.4.0\n\
+             Address: 192.168.1.20\n\
+             Request expires at 2026-10-18T10:05:00Z\n\
+             Allow this device to pair?"
+        );
+
+        let model_only = DevicePairingRequested {
+            device_model: Some("iPhone15,2".to_string()),
+            ..pairing_request("req-3")
+        };
+        assert!(pairing_description(&model_only).contains("\nDevice: iPhone15,2\n"));
+    }
+
+    #[test]
+    fn live_presence_overrides_the_stored_device_state() {
+        let state = AppState::with_backend(create_backend(BackendMode::Memory));
+        {
+            let mut runtime = state.bridge_runtime.lock().unwrap();
+            runtime.device_presence.insert(
+                "dev-online".to_string(),
+                DevicePresence {
+                    online: true,
+                    last_seen_at: Some("2026-10-18T10:00:00Z".to_string()),
+                },
+            );
+            runtime.device_presence.insert(
+                "dev-offline".to_string(),
+                DevicePresence {
+                    online: false,
+                    last_seen_at: None,
+                },
+            );
+        }
+        let mut devices = vec![
+            json!({ "deviceId": "dev-online", "online": false, "lastSeenAt": "2026-10-01T00:00:00Z" }),
+            json!({ "deviceId": "dev-offline", "online": true, "lastSeenAt": "2026-10-17T00:00:00Z" }),
+            json!({ "deviceId": "dev-unseen", "online": false }),
+            json!("not a device"),
+        ];
+
+        merge_live_presence(&state, &mut devices).unwrap();
+
+        assert_eq!(devices[0]["online"], true);
+        assert_eq!(devices[0]["lastSeenAt"], "2026-10-18T10:00:00Z");
+        assert_eq!(devices[1]["online"], false);
+        assert_eq!(devices[1]["lastSeenAt"], "2026-10-17T00:00:00Z");
+        assert_eq!(
+            devices[2],
+            json!({ "deviceId": "dev-unseen", "online": false })
+        );
+        assert_eq!(devices[3], json!("not a device"));
+    }
+
+    #[test]
+    fn device_and_pairing_ids_are_escaped_in_paths() {
+        assert_eq!(encode_path_segment("dev_1-a"), "dev_1-a");
+        assert_eq!(encode_path_segment("../a b/c"), "%2E%2E%2Fa%20b%2Fc");
+    }
+
+    #[test]
+    fn cancel_errors_name_the_device_when_known() {
+        let names = HashMap::from([("dev-1".to_string(), "Pixel 8".to_string())]);
+
+        let named = cancelled_by_error(&names, Some("client-9"), Some("dev-1"));
+        assert_eq!(named["message"], "Cancelled from Pixel 8");
+        assert_eq!(named["cancelledBy"]["deviceName"], "Pixel 8");
+        assert_eq!(named["cancelledBy"]["clientId"], "client-9");
+
+        let unnamed = cancelled_by_error(&names, Some("client-9"), Some("dev-2"));
+        assert_eq!(unnamed["message"], "Cancelled from device dev-2");
+        assert_eq!(unnamed["cancelledBy"]["deviceName"], Value::Null);
+
+        let loopback = cancelled_by_error(&names, Some("client-9"), None);
+        assert_eq!(
+            loopback["message"],
+            "Cancelled from another client (client-9)"
+        );
+        assert_eq!(
+            cancelled_by_error(&names, None, None)["message"],
+            "Cancelled from another device"
+        );
+    }
+}
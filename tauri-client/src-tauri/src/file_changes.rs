@@ -0,0 +1,247 @@
+// This module turns bridge diffs and recorded apply_patch calls into webview `fileChange` items.
+// Live runs send per-file unified diffs; replayed history only has the patch the agent wrote.
+
+use serde_json::{json, Value};
+
+use crate::git::diff_line_stats;
+
+fn change_json(path: &str, kind: &str, move_path: Option<&str>, diff: &str) -> Value {
+    let (additions, deletions) = diff_line_stats(diff);
+    json!({
+        "path": path,
+        "kind": { "type": kind, "move_path": move_path },
+        "diff": diff,
+        "additions": additions,
+        "deletions": deletions
+    })
+}
+
+fn kind_from_diff(diff: &str) -> &'static str {
+    if diff.lines().any(|line| line.starts_with("--- /dev/null")) {
+        "add"
+    } else if diff.lines().any(|line| line.starts_with("+++ /dev/null")) {
+        "delete"
+    } else {
+        "update"
+    }
+}
+
+pub(crate) fn diff_file_change(path: &str, diff: &str) -> Value {
+    change_json(path, kind_from_diff(diff), None, diff)
+}
+
+// Bridge diffs carry hunks only; the turn diff view expects `diff --git` sections.
+pub(crate) fn with_diff_headers(path: &str, diff: &str) -> String {
+    if diff.starts_with("diff --git ") {
+        return diff.to_string();
+    }
+    let mut section = format!("diff --git a/{path} b/{path}\n");
+    if !diff.starts_with("--- ") {
+        section.push_str(&format!("--- a/{path}\n+++ b/{path}\n"));
+    }
+    section.push_str(diff);
+    if !section.ends_with('\n') {
+        section.push('\n');
+    }
+    section
+}
+
+struct PatchFile {
+    path: String,
+    kind: &'static str,
+    move_path: Option<String>,
+    lines: Vec<String>,
+}
+
+impl PatchFile {
+    fn into_change(self) -> Value {
+        let (old_path, new_path) = match self.kind {
+            "add" => ("/dev/null".to_string(), format!("b/{}", self.path)),
+            "delete" => (format!("a/{}", self.path), "/dev/null".to_string()),
+            _ => (
+                format!("a/{}", self.path),
+                format!("b/{}", self.move_path.as_deref().unwrap_or(&self.path)),
+            ),
+        };
+        let mut diff = format!("--- {old_path}\n+++ {new_path}\n");
+        if self.kind == "add" && !self.lines.iter().any(|line| line.starts_with("@@")) {
+            diff.push_str(&format!("@@ -0,0 +1,{} @@\n", self.lines.len()));
+        }
+        for line in &self.lines {
+            diff.push_str(line);
+            diff.push('\n');
+        }
+        change_json(&self.path, self.kind, self.move_path.as_deref(), &diff)
+    }
+}
+
+// Parses the `*** Begin Patch` format the apply_patch tool takes into per-file changes.
+pub(crate) fn changes_from_apply_patch(patch: &str) -> Vec<Value> {
+    let mut files = Vec::new();
+    let mut current: Option<PatchFile> = None;
+    for line in patch.lines() {
+        let header = [
+            ("*** Add File: ", "add"),
+            ("*** Update File: ", "update"),
+            ("*** Delete File: ", "delete"),
+        ]
+        .into_iter()
+        .find_map(|(prefix, kind)| line.strip_prefix(prefix).map(|path| (path.trim(), kind)));
+        if let Some((path, kind)) = header {
+            files.extend(current.take());
+            current = Some(PatchFile {
+                path: path.to_string(),
+                kind,
+                move_path: None,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = current.as_mut() else {
+            continue;
+        };
+        if let Some(target) = line.strip_prefix("*** Move to: ") {
+            file.move_path = Some(target.trim().to_string());
+        } else if line.starts_with("*** ") {
+            continue;
+        } else if line.starts_with("@@")
+            || line.starts_with('+')
+            || line.starts_with('-')
+            || line.starts_with(' ')
+        {
+            file.lines.push(line.to_string());
+        }
+    }
+    files.extend(current);
+    files.into_iter().map(PatchFile::into_change).collect()
+}
+
+// apply_patch reports "Success. Updated the following files:" followed by `A|M|D path` lines.
+pub(crate) fn changes_from_patch_output(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (marker, path) = line.trim().split_once(' ')?;
+            let kind = match marker {
+                "A" => "add",
+                "M" => "update",
+                "D" => "delete",
+                _ => return None,
+            };
+            let path = path.trim();
+            if path.is_empty() {
+                return None;
+            }
+            Some(change_json(path, kind, None, ""))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_patch_add_file_gets_a_new_file_hunk() {
+        let patch =
+            "*** Begin Patch\n*** Add File: notes/todo.md\n+first\n+second\n*** End Patch\n";
+        let changes = changes_from_apply_patch(patch);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["path"], "notes/todo.md");
+        assert_eq!(changes[0]["kind"]["type"], "add");
+        assert_eq!(
+            changes[0]["diff"],
+            "--- /dev/null\n+++ b/notes/todo.md\n@@ -0,0 +1,2 @@\n+first\n+second\n"
+        );
+        assert_eq!(changes[0]["additions"], 2);
+        assert_eq!(changes[0]["deletions"], 0);
+    }
+
+    #[test]
+    fn apply_patch_update_with_move_keeps_hunks() {
+        let patch = "*** Begin Patch\n*** Update File: src/old.rs\n*** Move to: src/new.rs\n@@ fn main() {\n-    old();\n+    new();\n     done();\n*** End Patch\n";
+        let changes = changes_from_apply_patch(patch);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["path"], "src/old.rs");
+        assert_eq!(changes[0]["kind"]["type"], "update");
+        assert_eq!(changes[0]["kind"]["move_path"], "src/new.rs");
+        assert_eq!(
+            changes[0]["diff"],
+            "--- a/src/old.rs\n+++ b/src/new.rs\n@@ fn main() {\n-    old();\n+    new();\n     done();\n"
+        );
+        assert_eq!(changes[0]["additions"], 1);
+        assert_eq!(changes[0]["deletions"], 1);
+    }
+
+    #[test]
+    fn apply_patch_splits_multiple_files_in_order() {
+        let patch = "*** Begin Patch\n*** Delete File: gone.txt\n*** Update File: kept.txt\n@@\n-a\n+b\n*** Add File: fresh.txt\n+hello\n*** End Patch";
+        let changes = changes_from_apply_patch(patch);
+        let summary = changes
+            .iter()
+            .map(|change| {
+                (
+                    change["path"].as_str().unwrap_or_default(),
+                    change["kind"]["type"].as_str().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("gone.txt", "delete"),
+                ("kept.txt", "update"),
+                ("fresh.txt", "add")
+            ]
+        );
+        assert_eq!(changes[0]["diff"], "--- a/gone.txt\n+++ /dev/null\n");
+        assert_eq!(changes[0]["kind"]["move_path"], Value::Null);
+    }
+
+    #[test]
+    fn apply_patch_without_file_headers_has_no_changes() {
+        assert!(changes_from_apply_patch("*** Begin Patch\n+orphan\n*** End Patch\n").is_empty());
+    }
+
+    #[test]
+    fn patch_output_lists_touched_files() {
+        let output =
+            "Success. Updated the following files:\nA src/new.rs\nM src/lib.rs\nD old.txt\n";
+        let changes = changes_from_patch_output(output);
+        let summary = changes
+            .iter()
+            .map(|change| {
+                (
+                    change["path"].as_str().unwrap_or_default(),
+                    change["kind"]["type"].as_str().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("src/new.rs", "add"),
+                ("src/lib.rs", "update"),
+                ("old.txt", "delete")
+            ]
+        );
+    }
+
+    #[test]
+    fn bridge_diffs_get_git_headers_and_kinds() {
+        let hunk = "@@ -1 +1 @@\n-a\n+b";
+        assert_eq!(
+            with_diff_headers("src/a.rs", hunk),
+            "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1 +1 @@\n-a\n+b\n"
+        );
+        let full = "diff --git a/x b/x\n--- a/x\n+++ b/x\n";
+        assert_eq!(with_diff_headers("x", full), full);
+
+        let added = diff_file_change("new.rs", "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+x\n");
+        assert_eq!(added["kind"]["type"], "add");
+        let deleted =
+            diff_file_change("old.rs", "--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n");
+        assert_eq!(deleted["kind"]["type"], "delete");
+        assert_eq!(diff_file_change("a.rs", hunk)["kind"]["type"], "update");
+    }
+}
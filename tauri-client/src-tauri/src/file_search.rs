@@ -0,0 +1,445 @@
+// This module backs the @-mention file picker and the attachment picker.
+// Git roots are listed through `git ls-files`; other roots are walked with their ignore files applied.
+
+use futures_util::future::BoxFuture;
+use serde_json::{json, Map, Value};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Window;
+
+use crate::workers::{WorkerHandler, WorkerRequest, WorkerResult};
+use crate::{git, lock_or_err, normalize_root_path, normalize_root_string, AppState};
+
+pub(crate) const FILE_SEARCH_WORKER_ID: &str = "file-search";
+const FILE_LIST_CACHE_TTL: Duration = Duration::from_secs(10);
+const DEFAULT_FIND_FILES_LIMIT: usize = 50;
+const MAX_FIND_FILES_LIMIT: usize = 500;
+const MAX_WALKED_FILES: usize = 50_000;
+const WALK_SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    ".venv",
+    "__pycache__",
+];
+
+type CachedFileList = (Instant, Arc<Vec<String>>);
+
+#[derive(Default)]
+pub(crate) struct FileSearchState {
+    file_lists: Mutex<HashMap<String, CachedFileList>>,
+    in_flight: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+async fn list_root_files(root: &str) -> Vec<String> {
+    if git::repository_root(root).await.is_some() {
+        let listed = git::run_git(
+            Path::new(root),
+            &[
+                "ls-files",
+                "--cached",
+                "--others",
+                "--exclude-standard",
+                "-z",
+            ],
+        )
+        .await;
+        if let Ok(output) = listed {
+            return output
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(|path| path.replace('\\', "/"))
+                .collect();
+        }
+    }
+
+    let root = PathBuf::from(root);
+    tokio::task::spawn_blocking(move || walk_root_files(&root))
+        .await
+        .unwrap_or_default()
+}
+
+// Hidden files stay visible like in `git ls-files`; .gitignore and .ignore apply without a repo.
+fn walk_root_files(root: &Path) -> Vec<String> {
+    let walker = ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            !(entry.file_type().is_some_and(|kind| kind.is_dir())
+                && WALK_SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        files.push(relative.to_string_lossy().replace('\\', "/"));
+        if files.len() >= MAX_WALKED_FILES {
+            break;
+        }
+    }
+    files
+}
+
+async fn cached_root_files(state: &AppState, root: &str) -> Result<Arc<Vec<String>>, String> {
+    {
+        let lists = lock_or_err(&state.file_search.file_lists, "file_lists")?;
+        if let Some((fetched_at, files)) = lists.get(root) {
+            if fetched_at.elapsed() <= FILE_LIST_CACHE_TTL {
+                return Ok(files.clone());
+            }
+        }
+    }
+
+    let files = Arc::new(list_root_files(root).await);
+    let mut lists = lock_or_err(&state.file_search.file_lists, "file_lists")?;
+    lists.insert(root.to_string(), (Instant::now(), files.clone()));
+    Ok(files)
+}
+
+fn is_word_boundary(previous: Option<char>) -> bool {
+    matches!(previous, None | Some('/' | '\\' | '_' | '-' | '.' | ' '))
+}
+
+// Subsequence match with bonuses for consecutive runs, word starts and file-name hits.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate_chars = candidate.chars().collect::<Vec<_>>();
+    let lower_chars = candidate_chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect::<Vec<_>>();
+    let file_name_start = candidate
+        .rfind('/')
+        .map(|idx| candidate[..=idx].chars().count());
+
+    let mut score = 0i64;
+    let mut position = 0usize;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars() {
+        if query_char.is_whitespace() {
+            continue;
+        }
+        let found = (position..lower_chars.len()).find(|idx| lower_chars[*idx] == query_char)?;
+        score += 1;
+        if previous_match.is_some_and(|prev| prev + 1 == found) {
+            score += 5;
+        }
+        let previous_char = found.checked_sub(1).map(|idx| candidate_chars[idx]);
+        if is_word_boundary(previous_char) {
+            score += 8;
+        } else if candidate_chars[found].is_uppercase()
+            && previous_char.is_some_and(char::is_lowercase)
+        {
+            score += 6;
+        }
+        if file_name_start.is_none_or(|start| found >= start) {
+            score += 3;
+        }
+        previous_match = Some(found);
+        position = found + 1;
+    }
+
+    // Shorter paths win ties so `src/lib.rs` ranks above `vendor/x/src/lib.rs`.
+    score -= (candidate_chars.len() / 16) as i64;
+    Some(score)
+}
+
+fn search_root_files(
+    root: &str,
+    files: &[String],
+    query: &str,
+    limit: usize,
+    cancelled: &AtomicBool,
+) -> Vec<(i64, Value)> {
+    let mut matches = Vec::new();
+    for (idx, relative) in files.iter().enumerate() {
+        if idx % 1024 == 0 && cancelled.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
+        let Some(score) = fuzzy_score(query, relative) else {
+            continue;
+        };
+        let absolute = normalize_root_path(&Path::new(root).join(relative));
+        let file_name = relative.rsplit('/').next().unwrap_or(relative);
+        matches.push((
+            score,
+            json!({
+                "path": absolute,
+                "relativePath": relative,
+                "root": root,
+                "fileName": file_name,
+                "score": score
+            }),
+        ));
+    }
+    matches.sort_by_key(|(score, _)| Reverse(*score));
+    matches.truncate(limit);
+    matches
+}
+
+fn search_roots(
+    state: &AppState,
+    params: Option<&Map<String, Value>>,
+) -> Result<Vec<String>, String> {
+    let requested = params
+        .and_then(|p| p.get("roots").or_else(|| p.get("workspaceRoots")))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(normalize_root_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if !requested.is_empty() {
+        return Ok(requested);
+    }
+    if let Some(cwd) = params
+        .and_then(|p| p.get("cwd").or_else(|| p.get("root")))
+        .and_then(Value::as_str)
+        .filter(|cwd| !cwd.trim().is_empty())
+    {
+        return Ok(vec![normalize_root_string(cwd)]);
+    }
+
+    let workspace = lock_or_err(&state.workspace_state, "workspace_state")?;
+    if workspace.active_roots.is_empty() {
+        Ok(workspace.roots.clone())
+    } else {
+        Ok(workspace.active_roots.clone())
+    }
+}
+
+// Drops the request's cancel flag however the search ends, and stops a blocking scan whose
+// search future was dropped, e.g. by an aborted worker request.
+struct InFlightSearch<'a> {
+    state: &'a AppState,
+    request_id: &'a str,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for InFlightSearch<'_> {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        if let Ok(mut in_flight) = self.state.file_search.in_flight.lock() {
+            if in_flight
+                .get(self.request_id)
+                .is_some_and(|flag| Arc::ptr_eq(flag, &self.cancelled))
+            {
+                in_flight.remove(self.request_id);
+            }
+        }
+    }
+}
+
+// `on_matches` sees each root's best matches as soon as that root has been searched.
+pub(crate) async fn find_files(
+    state: &AppState,
+    request_id: &str,
+    params: Option<&Map<String, Value>>,
+    on_matches: impl Fn(&[Value]),
+) -> Result<Value, String> {
+    let query = params
+        .and_then(|p| p.get("query").or_else(|| p.get("pattern")))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let limit = params
+        .and_then(|p| p.get("limit").or_else(|| p.get("maxResults")))
+        .and_then(Value::as_u64)
+        .map(|v| v as usize)
+        .unwrap_or(DEFAULT_FIND_FILES_LIMIT)
+        .clamp(1, MAX_FIND_FILES_LIMIT);
+    let roots = search_roots(state, params)?;
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut in_flight = lock_or_err(&state.file_search.in_flight, "file_search_in_flight")?;
+        in_flight.insert(request_id.to_string(), cancelled.clone());
+    }
+    let search = InFlightSearch {
+        state,
+        request_id,
+        cancelled: cancelled.clone(),
+    };
+
+    let mut results = Vec::new();
+    for root in roots {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        let files = cached_root_files(state, &root).await?;
+        let search_query = query.clone();
+        let search_cancelled = cancelled.clone();
+        let matches = tokio::task::spawn_blocking(move || {
+            search_root_files(&root, &files, &search_query, limit, &search_cancelled)
+        })
+        .await
+        .map_err(|e| format!("find-files search failed: {e}"))?;
+        if !matches.is_empty() && !cancelled.load(Ordering::Relaxed) {
+            on_matches(
+                &matches
+                    .iter()
+                    .map(|(_, file)| file.clone())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        results.extend(matches);
+    }
+
+    let was_cancelled = cancelled.load(Ordering::Relaxed);
+    drop(search);
+    if was_cancelled {
+        return Ok(json!({ "files": [], "cancelled": true }));
+    }
+
+    results.sort_by_key(|(score, _)| Reverse(*score));
+    let truncated = results.len() > limit;
+    results.truncate(limit);
+    Ok(json!({
+        "files": results.into_iter().map(|(_, file)| file).collect::<Vec<_>>(),
+        "truncated": truncated
+    }))
+}
+
+pub(crate) fn cancel_find_files(state: &AppState, request_id: &str) -> Result<(), String> {
+    let in_flight = lock_or_err(&state.file_search.in_flight, "file_search_in_flight")?;
+    if let Some(cancelled) = in_flight.get(request_id) {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+pub(crate) struct FileSearchWorker;
+
+impl FileSearchWorker {
+    async fn handle_request(request: WorkerRequest) -> WorkerResult {
+        match request.method.as_str() {
+            "find-files" | "search" => {
+                // Worker cancels abort the task, which trips the search guard.
+                let request_id = format!("worker:{}", request.id);
+                find_files(
+                    request.state(),
+                    &request_id,
+                    request.params.as_object(),
+                    |files| request.send_progress(json!({ "files": files })),
+                )
+                .await
+            }
+            other => Err(format!("Unsupported file search worker method: {other}")),
+        }
+    }
+}
+
+impl WorkerHandler for FileSearchWorker {
+    fn handle(&self, request: WorkerRequest) -> BoxFuture<'static, WorkerResult> {
+        Box::pin(Self::handle_request(request))
+    }
+}
+
+pub(crate) async fn pick_files(window: &Window, params: Option<&Map<String, Value>>) -> Value {
+    let mut dialog = rfd::AsyncFileDialog::new().set_parent(window);
+    if let Some(title) = params.and_then(|p| p.get("title")).and_then(Value::as_str) {
+        dialog = dialog.set_title(title);
+    }
+    if let Some(directory) = params
+        .and_then(|p| p.get("defaultPath").or_else(|| p.get("cwd")))
+        .and_then(Value::as_str)
+        .filter(|path| Path::new(path).is_dir())
+    {
+        dialog = dialog.set_directory(directory);
+    }
+    if let Some(filters) = params
+        .and_then(|p| p.get("filters"))
+        .and_then(Value::as_array)
+    {
+        for filter in filters {
+            let name = filter
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("Files");
+            let extensions = filter
+                .get("extensions")
+                .and_then(Value::as_array)
+                .map(|items| items.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+                .unwrap_or_default();
+            if !extensions.is_empty() {
+                dialog = dialog.add_filter(name, &extensions);
+            }
+        }
+    }
+
+    let multiple = params
+        .and_then(|p| p.get("multiple").or_else(|| p.get("allowMultiple")))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let picked = if multiple {
+        dialog.pick_files().await.unwrap_or_default()
+    } else {
+        dialog.pick_file().await.into_iter().collect()
+    };
+
+    let paths = picked
+        .iter()
+        .map(|file| normalize_root_path(file.path()))
+        .collect::<Vec<_>>();
+    json!({ "paths": paths })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_requires_an_ordered_subsequence() {
+        assert!(fuzzy_score("lib", "src/lib.rs").is_some());
+        assert!(fuzzy_score("LIB", "src/lib.rs").is_some());
+        assert!(fuzzy_score("bil", "src/lib.rs").is_none());
+        assert!(fuzzy_score("libx", "src/lib.rs").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_ignores_whitespace_in_query() {
+        assert_eq!(
+            fuzzy_score("src lib", "src/lib.rs"),
+            fuzzy_score("srclib", "src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_file_name_and_boundary_hits() {
+        let file_name = fuzzy_score("main", "src/main.rs").unwrap();
+        let scattered = fuzzy_score("main", "src/my_app/index.rs").unwrap();
+        assert!(file_name > scattered);
+
+        let camel = fuzzy_score("fs", "src/FileSearch.ts").unwrap();
+        let inner = fuzzy_score("fs", "src/offsets.ts").unwrap();
+        assert!(camel > inner);
+    }
+
+    #[test]
+    fn fuzzy_score_ranks_shorter_paths_first() {
+        let short = fuzzy_score("lib.rs", "src/lib.rs").unwrap();
+        let long = fuzzy_score("lib.rs", "vendor/some/crate/with/src/lib.rs").unwrap();
+        assert!(short > long);
+    }
+}
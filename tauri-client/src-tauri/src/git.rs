@@ -0,0 +1,597 @@
+// This module answers git questions about workspace roots by shelling out to `git`.
+// Results feed thread gitInfo, the `git` worker and the vscode://codex/ git endpoints.
+
+use futures_util::future::{join_all, BoxFuture};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+use crate::workers::{WorkerHandler, WorkerRequest, WorkerResult};
+use crate::{lock_or_err, AppState};
+
+pub(crate) const GIT_WORKER_ID: &str = "git";
+const GIT_INFO_CACHE_TTL: Duration = Duration::from_secs(15);
+const GIT_COMMAND_TIMEOUT: Duration = Duration::from_secs(20);
+const MAX_UNTRACKED_DIFF_BYTES: u64 = 256 * 1024;
+
+#[derive(Default)]
+pub(crate) struct GitInfoCache {
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+pub(crate) async fn run_git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    run_git_with_env(cwd, args, &[]).await
+}
+
+pub(crate) async fn run_git_with_env(
+    cwd: &Path,
+    args: &[&str],
+    envs: &[(&str, &Path)],
+) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command
+        .args(args)
+        .envs(envs.iter().copied())
+        .current_dir(cwd)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(GIT_COMMAND_TIMEOUT, command.output())
+        .await
+        .map_err(|_| format!("git {} timed out", args.join(" ")))?
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            format!("git {} exited with {}", args.join(" "), output.status)
+        } else {
+            stderr
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn run_git_line(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = run_git(cwd, args).await.ok()?;
+    let line = output.trim();
+    if line.is_empty() {
+        None
+    } else {
+        Some(line.to_string())
+    }
+}
+
+pub(crate) async fn repository_root(cwd: &str) -> Option<String> {
+    let path = Path::new(cwd);
+    if !path.is_dir() {
+        return None;
+    }
+    run_git_line(path, &["rev-parse", "--show-toplevel"]).await
+}
+
+async fn current_branch(root: &Path) -> Option<String> {
+    run_git_line(root, &["rev-parse", "--abbrev-ref", "HEAD"])
+        .await
+        .filter(|branch| branch != "HEAD")
+}
+
+async fn head_sha(root: &Path) -> Option<String> {
+    run_git_line(root, &["rev-parse", "HEAD"]).await
+}
+
+async fn origin_url(root: &Path) -> Option<String> {
+    run_git_line(root, &["config", "--get", "remote.origin.url"]).await
+}
+
+async fn read_git_info(cwd: &str) -> Value {
+    let Some(root) = repository_root(cwd).await else {
+        return Value::Null;
+    };
+    let root = Path::new(&root);
+    let (sha, branch, origin) =
+        futures_util::join!(head_sha(root), current_branch(root), origin_url(root));
+    json!({
+        "sha": sha,
+        "branch": branch,
+        "originUrl": origin
+    })
+}
+
+fn cached_git_info(state: &AppState, cwd: &str) -> Option<Value> {
+    let cache = state.git_info_cache.entries.lock().ok()?;
+    let (fetched_at, value) = cache.get(cwd)?;
+    if fetched_at.elapsed() > GIT_INFO_CACHE_TTL {
+        return None;
+    }
+    Some(value.clone())
+}
+
+pub(crate) async fn git_info_for_cwd(state: &AppState, cwd: &str) -> Value {
+    if let Some(value) = cached_git_info(state, cwd) {
+        return value;
+    }
+    let value = read_git_info(cwd).await;
+    if let Ok(mut cache) = state.git_info_cache.entries.lock() {
+        cache.insert(cwd.to_string(), (Instant::now(), value.clone()));
+    }
+    value
+}
+
+pub(crate) async fn git_info_by_cwd(state: &AppState, cwds: &[String]) -> HashMap<String, Value> {
+    let mut unique = cwds.to_vec();
+    unique.sort();
+    unique.dedup();
+    let values = join_all(unique.iter().map(|cwd| git_info_for_cwd(state, cwd))).await;
+    unique.into_iter().zip(values).collect()
+}
+
+pub(crate) async fn git_origins(dirs: &[String]) -> Value {
+    let origins = join_all(dirs.iter().map(|dir| async move {
+        let root = repository_root(dir).await?;
+        let origin = origin_url(Path::new(&root)).await;
+        Some(json!({
+            "dir": dir,
+            "root": root,
+            "originUrl": origin
+        }))
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    json!({ "origins": origins })
+}
+
+fn porcelain_status_label(code: char) -> Option<&'static str> {
+    match code {
+        'M' => Some("modified"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        'U' => Some("conflicted"),
+        'T' => Some("typeChanged"),
+        '?' => Some("untracked"),
+        _ => None,
+    }
+}
+
+fn parse_porcelain_status(output: &str) -> Vec<Value> {
+    let mut files = Vec::new();
+    let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let mut codes = entry.chars();
+        let index_code = codes.next().unwrap_or(' ');
+        let worktree_code = codes.next().unwrap_or(' ');
+        let path = entry[3..].to_string();
+        let original_path = if matches!(index_code, 'R' | 'C') {
+            entries.next().map(ToString::to_string)
+        } else {
+            None
+        };
+        let status = porcelain_status_label(index_code)
+            .or_else(|| porcelain_status_label(worktree_code))
+            .unwrap_or("modified");
+
+        files.push(json!({
+            "path": path,
+            "originalPath": original_path,
+            "status": status,
+            "staged": index_code != ' ' && index_code != '?',
+            "unstaged": worktree_code != ' '
+        }));
+    }
+
+    files
+}
+
+async fn ahead_behind(root: &Path) -> (Option<u64>, Option<u64>) {
+    let Some(counts) = run_git_line(
+        root,
+        &["rev-list", "--left-right", "--count", "@{upstream}...HEAD"],
+    )
+    .await
+    else {
+        return (None, None);
+    };
+    let mut parts = counts.split_whitespace();
+    let behind = parts.next().and_then(|v| v.parse::<u64>().ok());
+    let ahead = parts.next().and_then(|v| v.parse::<u64>().ok());
+    (ahead, behind)
+}
+
+pub(crate) async fn git_status(cwd: &str) -> Result<Value, String> {
+    let root = repository_root(cwd)
+        .await
+        .ok_or_else(|| format!("{cwd} is not inside a git repository"))?;
+    let root_path = Path::new(&root);
+    let porcelain = run_git(root_path, &["status", "--porcelain=v1", "-z"]).await?;
+    let files = parse_porcelain_status(&porcelain);
+    let (sha, branch, origin) = futures_util::join!(
+        head_sha(root_path),
+        current_branch(root_path),
+        origin_url(root_path)
+    );
+    let upstream = run_git_line(
+        root_path,
+        &[
+            "rev-parse",
+            "--abbrev-ref",
+            "--symbolic-full-name",
+            "@{upstream}",
+        ],
+    )
+    .await;
+    let (ahead, behind) = ahead_behind(root_path).await;
+
+    Ok(json!({
+        "root": root,
+        "branch": branch,
+        "sha": sha,
+        "originUrl": origin,
+        "upstream": upstream,
+        "ahead": ahead,
+        "behind": behind,
+        "dirty": !files.is_empty(),
+        "files": files
+    }))
+}
+
+pub(crate) async fn git_branch_info(cwd: &str) -> Result<Value, String> {
+    let root = repository_root(cwd)
+        .await
+        .ok_or_else(|| format!("{cwd} is not inside a git repository"))?;
+    let root_path = Path::new(&root);
+    let (sha, branch) = futures_util::join!(head_sha(root_path), current_branch(root_path));
+    let branches = run_git(
+        root_path,
+        &["for-each-ref", "--format=%(refname:short)", "refs/heads"],
+    )
+    .await
+    .map(|output| {
+        output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+
+    Ok(json!({
+        "root": root,
+        "branch": branch,
+        "sha": sha,
+        "detached": branch.is_none() && sha.is_some(),
+        "branches": branches
+    }))
+}
+
+// `---`/`+++` are file headers only before a section's first hunk; inside hunks they are
+// content lines such as `-- SQL comment` or `++i;`.
+pub(crate) fn diff_line_stats(diff: &str) -> (usize, usize) {
+    let mut additions = 0;
+    let mut deletions = 0;
+    let mut in_header = true;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            in_header = true;
+            continue;
+        }
+        if line.starts_with("@@") {
+            in_header = false;
+            continue;
+        }
+        if in_header && (line.starts_with("+++") || line.starts_with("---")) {
+            continue;
+        }
+        if line.starts_with('+') {
+            additions += 1;
+        } else if line.starts_with('-') {
+            deletions += 1;
+        }
+    }
+    (additions, deletions)
+}
+
+fn diff_section_path(section: &str) -> Option<String> {
+    for line in section.lines() {
+        if let Some(path) = line.strip_prefix("+++ b/") {
+            return Some(path.to_string());
+        }
+        if line.starts_with("+++ /dev/null") {
+            break;
+        }
+    }
+    let header = section.lines().next()?;
+    let (_, new_path) = header.strip_prefix("diff --git a/")?.split_once(" b/")?;
+    Some(new_path.to_string())
+}
+
+pub(crate) fn split_unified_diff(patch: &str) -> Vec<Value> {
+    let mut sections = Vec::new();
+    let mut current = String::new();
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("diff --git ") && !current.is_empty() {
+            sections.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        sections.push(current);
+    }
+
+    sections
+        .into_iter()
+        .filter_map(|section| {
+            let path = diff_section_path(&section)?;
+            let (additions, deletions) = diff_line_stats(&section);
+            Some(json!({
+                "path": path,
+                "diff": section,
+                "additions": additions,
+                "deletions": deletions
+            }))
+        })
+        .collect()
+}
+
+// Mirrors `git diff --no-index /dev/null <file>`; an empty file has a header but no hunk.
+fn new_file_diff(relative: &str, content: &str) -> String {
+    let mut diff = format!("diff --git a/{relative} b/{relative}\nnew file mode 100644\n");
+    if content.is_empty() {
+        return diff;
+    }
+    let lines = content.lines().collect::<Vec<_>>();
+    diff.push_str(&format!(
+        "--- /dev/null\n+++ b/{relative}\n@@ -0,0 +1,{} @@\n",
+        lines.len()
+    ));
+    for line in &lines {
+        diff.push('+');
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    if !content.ends_with('\n') {
+        diff.push_str("\\ No newline at end of file\n");
+    }
+    diff
+}
+
+async fn untracked_file_diff(root: &Path, relative: &str) -> Option<Value> {
+    let path = root.join(relative);
+    let metadata = tokio::fs::metadata(&path).await.ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_UNTRACKED_DIFF_BYTES {
+        return None;
+    }
+    let content = tokio::fs::read_to_string(&path).await.ok()?;
+    let diff = new_file_diff(relative, &content);
+    let (additions, _) = diff_line_stats(&diff);
+    Some(json!({
+        "path": relative,
+        "diff": diff,
+        "additions": additions,
+        "deletions": 0
+    }))
+}
+
+// `on_files` sees the tracked diffs and then the untracked files as each batch is ready.
+pub(crate) async fn git_diff(
+    cwd: &str,
+    paths: &[String],
+    on_files: impl Fn(&[Value]),
+) -> Result<Vec<Value>, String> {
+    let root = repository_root(cwd)
+        .await
+        .ok_or_else(|| format!("{cwd} is not inside a git repository"))?;
+    let root_path = Path::new(&root);
+    let has_head = head_sha(root_path).await.is_some();
+
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+    if has_head {
+        args.push("HEAD");
+    } else {
+        args.push("--cached");
+    }
+    args.push("--");
+    args.extend(paths.iter().map(String::as_str));
+    let patch = run_git(root_path, &args).await?;
+    let mut files = split_unified_diff(&patch);
+    on_files(&files);
+
+    let mut untracked_args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+    untracked_args.extend(paths.iter().map(String::as_str));
+    let untracked = run_git(root_path, &untracked_args)
+        .await
+        .unwrap_or_default();
+    let untracked_diffs = join_all(
+        untracked
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| untracked_file_diff(root_path, path)),
+    )
+    .await
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !untracked_diffs.is_empty() {
+        on_files(&untracked_diffs);
+    }
+    files.extend(untracked_diffs);
+
+    Ok(files)
+}
+
+fn request_cwd(state: &AppState, params: &Value) -> String {
+    params
+        .get("root")
+        .or_else(|| params.get("cwd"))
+        .or_else(|| params.get("dir"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|cwd| !cwd.is_empty())
+        .map(crate::normalize_root_string)
+        .unwrap_or_else(|| crate::preferred_workspace_cwd(state))
+}
+
+fn request_paths(params: &Value) -> Vec<String> {
+    params
+        .get("paths")
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn request_dirs(
+    state: &AppState,
+    params: Option<&Value>,
+) -> Result<Vec<String>, String> {
+    let dirs = params
+        .and_then(|p| p.get("dirs").or_else(|| p.get("roots")))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(crate::normalize_root_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if !dirs.is_empty() {
+        return Ok(dirs);
+    }
+    let workspace = lock_or_err(&state.workspace_state, "workspace_state")?;
+    Ok(workspace.roots.clone())
+}
+
+pub(crate) struct GitWorker;
+
+impl GitWorker {
+    async fn handle_request(request: WorkerRequest) -> WorkerResult {
+        let state = request.state();
+        match request.method.as_str() {
+            "origins" | "git-origins" => {
+                let dirs = request_dirs(state, Some(&request.params))?;
+                Ok(git_origins(&dirs).await)
+            }
+            "status" | "git-status" => git_status(&request_cwd(state, &request.params)).await,
+            "branch" | "branch-info" | "current-branch" => {
+                git_branch_info(&request_cwd(state, &request.params)).await
+            }
+            "git-info" => Ok(git_info_for_cwd(state, &request_cwd(state, &request.params)).await),
+            "diff" | "git-diff" => {
+                let cwd = request_cwd(state, &request.params);
+                let paths = request_paths(&request.params);
+                let files = git_diff(&cwd, &paths, |files| {
+                    request.send_progress(json!({ "files": files }));
+                })
+                .await?;
+                Ok(json!({ "files": files }))
+            }
+            other => Err(format!("Unsupported git worker method: {other}")),
+        }
+    }
+}
+
+impl WorkerHandler for GitWorker {
+    fn handle(&self, request: WorkerRequest) -> BoxFuture<'static, WorkerResult> {
+        Box::pin(Self::handle_request(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_status_reports_renames_and_untracked_files() {
+        let output = "M  src/lib.rs\0 M README.md\0R  new.rs\0old.rs\0?? notes.txt\0";
+        let files = parse_porcelain_status(output);
+        assert_eq!(files.len(), 4);
+
+        assert_eq!(files[0]["path"], "src/lib.rs");
+        assert_eq!(files[0]["status"], "modified");
+        assert_eq!(files[0]["staged"], true);
+        assert_eq!(files[0]["unstaged"], false);
+
+        assert_eq!(files[1]["path"], "README.md");
+        assert_eq!(files[1]["staged"], false);
+        assert_eq!(files[1]["unstaged"], true);
+
+        assert_eq!(files[2]["path"], "new.rs");
+        assert_eq!(files[2]["originalPath"], "old.rs");
+        assert_eq!(files[2]["status"], "renamed");
+
+        assert_eq!(files[3]["path"], "notes.txt");
+        assert_eq!(files[3]["status"], "untracked");
+        assert_eq!(files[3]["staged"], false);
+    }
+
+    #[test]
+    fn porcelain_status_of_clean_tree_is_empty() {
+        assert!(parse_porcelain_status("").is_empty());
+    }
+
+    #[test]
+    fn line_stats_skip_file_headers() {
+        let diff = "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-old\n+new\n+more\n context\n";
+        assert_eq!(diff_line_stats(diff), (2, 1));
+    }
+
+    #[test]
+    fn line_stats_count_hunk_lines_that_look_like_headers() {
+        let diff = "diff --git a/q.sql b/q.sql\n--- a/q.sql\n+++ b/q.sql\n@@ -1,2 +1,2 @@\n--- old comment\n+++i;\n context\n\
+                    diff --git a/b.txt b/b.txt\n--- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-x\n+y\n";
+        assert_eq!(diff_line_stats(diff), (2, 2));
+    }
+
+    #[test]
+    fn unified_diff_splits_per_file() {
+        let patch = "diff --git a/a.txt b/a.txt\nindex 1..2 100644\n--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-a\n+b\n\
+diff --git a/gone.txt b/gone.txt\ndeleted file mode 100644\n--- a/gone.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-x\n";
+        let files = split_unified_diff(patch);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["path"], "a.txt");
+        assert_eq!(files[0]["additions"], 1);
+        assert_eq!(files[0]["deletions"], 1);
+        assert_eq!(files[1]["path"], "gone.txt");
+        assert_eq!(files[1]["additions"], 0);
+        assert_eq!(files[1]["deletions"], 1);
+        assert!(files[1]["diff"]
+            .as_str()
+            .unwrap()
+            .starts_with("diff --git a/gone.txt"));
+    }
+
+    #[test]
+    fn new_file_diff_of_empty_file_has_no_hunk() {
+        let diff = new_file_diff("empty.txt", "");
+        assert!(!diff.contains("@@"));
+        assert_eq!(split_unified_diff(&diff)[0]["path"], "empty.txt");
+    }
+
+    #[test]
+    fn new_file_diff_marks_missing_trailing_newline() {
+        let diff = new_file_diff("a.txt", "one\ntwo");
+        assert!(diff.contains("@@ -0,0 +1,2 @@\n+one\n+two\n\\ No newline at end of file\n"));
+        assert_eq!(diff_line_stats(&diff), (2, 0));
+    }
+}
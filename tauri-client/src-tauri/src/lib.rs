@@ -1,28 +1,59 @@
 // This module provides a compatibility host for the migrated Codex webview.
 // It keeps the Electron message shape so the existing frontend can boot in Tauri.
 
+mod app_server;
+mod approval_rules;
+mod approvals;
+mod attachments;
+mod backend;
+mod bridge_protocol;
+mod context_menu;
+mod devices;
+mod file_changes;
+mod file_search;
+mod git;
+mod open_targets;
+mod turn_snapshots;
+mod workers;
+
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use futures_util::{SinkExt, StreamExt};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::env;
 use std::fs;
 use std::net::TcpListener;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::{Emitter, Manager, State, Window};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message as WsMessage;
 use url::Url;
 use uuid::Uuid;
 
+use app_server::AppServerState;
+use approval_rules::ApprovalRuleStore;
+use approvals::{PendingApproval, SessionApprovalRules};
+use backend::{BackendHost, McpBackend};
+use bridge_protocol::{
+    decode_bridge_event, emit_bridge_diagnostic, report_bridge_diagnostic, BridgeDecodeError,
+    BridgeEvent, BridgeServerInfo, PlanStep, TurnPlanSnapshot, BRIDGE_PROTOCOL_VERSION,
+};
+use context_menu::ContextMenuState;
+use file_search::FileSearchState;
+use git::GitInfoCache;
+use open_targets::OpenTargetState;
+use turn_snapshots::TurnSnapshotStore;
+use workers::WorkerRegistry;
+
 const CHANNEL_MESSAGE_FOR_VIEW: &str = "codex_desktop:message-for-view";
 const VSCODE_FETCH_PREFIX: &str = "vscode://codex/";
 const GLOBAL_KEY_ACTIVE_WORKSPACE_ROOTS: &str = "active-workspace-roots";
@@ -31,6 +62,15 @@ const GLOBAL_KEY_WORKSPACE_ROOT_LABELS: &str = "electron-workspace-root-labels";
 const GLOBAL_KEY_PINNED_THREAD_IDS: &str = "pinned-thread-ids";
 const DEFAULT_BRIDGE_HEALTH_PATH: &str = "api/v1/health";
 const DEFAULT_BRIDGE_WS_PATH: &str = "ws";
+const BRIDGE_COMMAND_TIMEOUT: Duration = Duration::from_secs(15);
+const BRIDGE_ERROR_HISTORY_LIMIT: usize = 50;
+const BRIDGE_HISTORY_PAGE_SIZE: usize = 50;
+// A replayed turn is usually one user message and one reply.
+const BRIDGE_MESSAGES_PER_TURN: usize = 2;
+// The bridge caps one sessions listing at this many entries.
+const BRIDGE_SESSION_LIST_PAGE_SIZE: usize = 200;
+// Session ids end up as a URL path segment; `.` stays encoded so an id can never read as `..`.
+const SESSION_ID_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_');
 const CONFIG_KEY_MODEL: &str = "model";
 const CONFIG_KEY_MODEL_EFFORT: &str = "model_reasoning_effort";
 const CONFIG_KEY_APPROVAL_POLICY: &str = "approval_policy";
@@ -128,7 +168,75 @@ struct BridgeRuntimeState {
     process: Option<Child>,
     run_states: HashMap<String, RunBridgeState>,
     pending_turns: HashMap<String, VecDeque<String>>,
-    approval_run_by_request: HashMap<String, String>,
+    pending_approvals: HashMap<String, PendingApproval>,
+    session_approval_rules: HashMap<String, SessionApprovalRules>,
+    server_info: BridgeServerInfo,
+    protocol_version: Option<u32>,
+    incompatibility: Option<String>,
+    client_id: Option<String>,
+    pending_commands: VecDeque<PendingBridgeCommand>,
+    recent_errors: VecDeque<Value>,
+    thread_activity: HashMap<String, i64>,
+    device_presence: HashMap<String, DevicePresence>,
+    // Paired device names by device id, from the server's device list.
+    device_names: HashMap<String, String>,
+    // Latest plan per session, kept current by `run.plan.updated`; `None` means the bridge has none.
+    latest_plans: HashMap<String, Option<TurnPlanSnapshot>>,
+    // Converted history per session; dropped whenever the session sees new activity.
+    history_cache: HashMap<String, BridgeHistoryCache>,
+}
+
+impl BridgeRuntimeState {
+    // Events without a clientId, or before `bridge.connected`, are treated as our own.
+    fn is_other_client(&self, client_id: Option<&str>) -> bool {
+        match (client_id, self.client_id.as_deref()) {
+            (Some(sender), Some(own)) => sender != own,
+            _ => false,
+        }
+    }
+
+    // Unlike `!is_other_client`, an event without a client id is never ours.
+    fn is_own_client(&self, client_id: Option<&str>) -> bool {
+        client_id.is_some() && client_id == self.client_id.as_deref()
+    }
+}
+
+struct DevicePresence {
+    online: bool,
+    last_seen_at: Option<String>,
+}
+
+// The bridge does not echo command ids, so replies are matched on name, target and sending client.
+struct PendingBridgeCommand {
+    id: String,
+    name: String,
+    session_id: Option<String>,
+    run_id: Option<String>,
+    request_id: Option<String>,
+    reply: oneshot::Sender<Result<Value, String>>,
+}
+
+impl PendingBridgeCommand {
+    fn targets(
+        &self,
+        session_id: Option<&str>,
+        run_id: Option<&str>,
+        request_id: Option<&str>,
+    ) -> bool {
+        // An id the command recorded must come back unchanged; ids it never sent match anything.
+        let matches = |own: &Option<String>, other: Option<&str>| {
+            own.as_deref().is_none_or(|own| other == Some(own))
+        };
+        matches(&self.session_id, session_id)
+            && matches(&self.run_id, run_id)
+            && matches(&self.request_id, request_id)
+    }
+}
+
+// The other client that cancelled a run, as reported by `run.cancel.requested`.
+struct CancelSource {
+    client_id: Option<String>,
+    device_id: Option<String>,
 }
 
 #[derive(Default)]
@@ -140,6 +248,10 @@ struct RunBridgeState {
     completed_items: HashSet<String>,
     assistant_delta_seen: bool,
     item_payloads: HashMap<String, Value>,
+    cancelled_by: Option<CancelSource>,
+    // Latest unified diff per path, used for the turn-level diff view.
+    file_diffs: BTreeMap<String, String>,
+    diff_updates: usize,
 }
 
 #[derive(Clone, Default)]
@@ -158,11 +270,40 @@ struct BridgeSessionSummary {
     title: String,
     #[serde(default)]
     cwd: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+impl BridgeSessionSummary {
+    // Live run activity is newer than the session file's write time the bridge reports.
+    fn timestamps(&self, state: &AppState) -> (i64, i64) {
+        let created_at = self
+            .created_at
+            .as_deref()
+            .and_then(parse_iso8601_unix_seconds);
+        let updated_at = self
+            .updated_at
+            .as_deref()
+            .and_then(parse_iso8601_unix_seconds)
+            .or(created_at);
+        let updated_at = match (updated_at, bridge_thread_activity(state, &self.id)) {
+            (Some(stored), Some(live)) => Some(stored.max(live)),
+            (stored, live) => stored.or(live),
+        };
+        let now = now_unix_seconds();
+        let created_at = created_at.or(updated_at).unwrap_or(now);
+        (created_at, updated_at.unwrap_or(created_at))
+    }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct BridgeSessionMessage {
+    // Position in the whole session; bridges that predate paging omit it.
+    #[serde(default)]
+    ordinal: Option<usize>,
     role: String,
     #[serde(default)]
     text: String,
@@ -179,6 +320,8 @@ struct BridgeSessionTraceEntry {
     #[serde(default)]
     text: Option<String>,
     #[serde(default)]
+    tool: Option<String>,
+    #[serde(default)]
     command: Option<String>,
     #[serde(default)]
     status: Option<String>,
@@ -186,6 +329,73 @@ struct BridgeSessionTraceEntry {
     exit_code: Option<i32>,
     #[serde(default)]
     output: Option<String>,
+    // The raw apply_patch input, present only on apply_patch entries.
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+// How a replayed trace entry renders; the bridge records every function call as kind "command".
+enum TraceEntryKind<'a> {
+    Reasoning,
+    Command,
+    FileChange,
+    Plan,
+    WebSearch,
+    McpToolCall { server: &'a str, tool: &'a str },
+}
+
+impl BridgeSessionTraceEntry {
+    fn render_kind(&self) -> Option<TraceEntryKind<'_>> {
+        let kind = self.kind.trim();
+        if kind.eq_ignore_ascii_case("reasoning") {
+            return Some(TraceEntryKind::Reasoning);
+        }
+        if kind.eq_ignore_ascii_case("fileChange") {
+            return Some(TraceEntryKind::FileChange);
+        }
+        if kind.eq_ignore_ascii_case("plan") {
+            return Some(TraceEntryKind::Plan);
+        }
+        if kind.eq_ignore_ascii_case("webSearch") {
+            return Some(TraceEntryKind::WebSearch);
+        }
+        if !kind.eq_ignore_ascii_case("command") && !kind.eq_ignore_ascii_case("tool") {
+            return None;
+        }
+
+        let tool = self.tool.as_deref().map(str::trim).unwrap_or_default();
+        match tool {
+            "apply_patch" => Some(TraceEntryKind::FileChange),
+            "update_plan" => Some(TraceEntryKind::Plan),
+            "web_search" | "web_search_call" | "search" => Some(TraceEntryKind::WebSearch),
+            _ => {
+                // Codex qualifies MCP tools as `mcp__<server>__<tool>` or `<server>__<tool>`.
+                let qualified = tool.strip_prefix("mcp__").unwrap_or(tool);
+                match qualified.split_once("__") {
+                    Some((server, name)) if !server.is_empty() && !name.is_empty() => {
+                        Some(TraceEntryKind::McpToolCall { server, tool: name })
+                    }
+                    _ => Some(TraceEntryKind::Command),
+                }
+            }
+        }
+    }
+
+    fn trimmed_text(&self) -> Option<String> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    }
+
+    fn failed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+            || self
+                .status
+                .as_deref()
+                .is_some_and(|status| status.eq_ignore_ascii_case("failed"))
+    }
 }
 
 struct AppState {
@@ -196,15 +406,35 @@ struct AppState {
     workspace_state: Mutex<WorkspaceState>,
     thread_store: Mutex<ThreadStore>,
     bridge_runtime: Mutex<BridgeRuntimeState>,
+    backend: Arc<dyn McpBackend>,
+    app_server: AppServerState,
+    context_menu: ContextMenuState,
+    workers: WorkerRegistry,
+    git_info_cache: GitInfoCache,
+    file_search: FileSearchState,
+    open_targets: OpenTargetState,
+    turn_snapshots: TurnSnapshotStore,
+    approval_rules: ApprovalRuleStore,
 }
 
 impl AppState {
     fn new() -> Self {
+        Self::with_backend(backend::create_backend(backend::resolve_backend_mode()))
+    }
+
+    fn with_backend(backend: Arc<dyn McpBackend>) -> Self {
         let build_number = env::var("CODEX_BUILD_NUMBER")
             .ok()
             .map(|v| v.trim().to_owned())
             .filter(|v| !v.is_empty());
 
+        let mut workers = WorkerRegistry::default();
+        workers.register(git::GIT_WORKER_ID, Arc::new(git::GitWorker));
+        workers.register(
+            file_search::FILE_SEARCH_WORKER_ID,
+            Arc::new(file_search::FileSearchWorker),
+        );
+
         Self {
             bridge_meta: BridgeMeta {
                 build_flavor: "tauri".to_string(),
@@ -218,6 +448,15 @@ impl AppState {
             workspace_state: Mutex::new(WorkspaceState::default()),
             thread_store: Mutex::new(ThreadStore::default()),
             bridge_runtime: Mutex::new(BridgeRuntimeState::default()),
+            backend,
+            app_server: AppServerState::default(),
+            context_menu: ContextMenuState::default(),
+            workers,
+            git_info_cache: GitInfoCache::default(),
+            file_search: FileSearchState::default(),
+            open_targets: OpenTargetState::default(),
+            turn_snapshots: TurnSnapshotStore::default(),
+            approval_rules: ApprovalRuleStore::default(),
         }
     }
 }
@@ -570,6 +809,13 @@ fn now_unix_seconds() -> i64 {
         .unwrap_or(0)
 }
 
+// Parses the ISO 8601 timestamps System.Text.Json writes for DateTimeOffset, e.g. 2025-01-02T03:04:05.678+08:00.
+fn parse_iso8601_unix_seconds(raw: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(raw.trim())
+        .ok()
+        .map(|timestamp| timestamp.timestamp())
+}
+
 fn default_thread_source() -> Value {
     json!({ "kind": "local" })
 }
@@ -619,6 +865,10 @@ fn create_host_thread(id: Option<String>, cwd: String, preview: String) -> HostT
     }
 }
 
+fn set_host_thread_git_info(thread: &mut HostThread, git_info: Value) {
+    thread.git_info = Some(git_info).filter(|info| !info.is_null());
+}
+
 fn promote_thread_in_order(store: &mut ThreadStore, thread_id: &str) {
     store.order.retain(|id| id != thread_id);
     store.order.insert(0, thread_id.to_string());
@@ -762,6 +1012,11 @@ fn json_fetch_error(request_id: &str, status: u16, error: impl Into<String>) ->
     })
 }
 
+// Host endpoints under vscode://codex/ report failures as error fetch responses.
+fn emit_fetch_error(window: &Window, request_id: &str, error: String) -> Result<(), String> {
+    emit_message_to_window(window, json_fetch_error(request_id, 500, error))
+}
+
 fn json_fetch_success(
     request_id: &str,
     status: u16,
@@ -977,7 +1232,7 @@ fn find_bridge_server_executable() -> Option<PathBuf> {
         .find(|path| path.exists())
 }
 
-async fn bridge_health_check(base_url: &str) -> bool {
+async fn bridge_health_check(base_url: &str) -> Option<BridgeServerInfo> {
     let health_url = format!(
         "{}/{}",
         base_url.trim_end_matches('/'),
@@ -985,11 +1240,39 @@ async fn bridge_health_check(base_url: &str) -> bool {
     );
     let client = reqwest::Client::new();
     match client.get(health_url).send().await {
-        Ok(resp) => resp.status().is_success(),
-        Err(_) => false,
+        Ok(resp) if resp.status().is_success() => {
+            Some(resp.json::<BridgeServerInfo>().await.unwrap_or_default())
+        }
+        _ => None,
+    }
+}
+
+// Health metadata and `bridge.connected` both feed this; the latest advertisement wins field by field.
+fn record_bridge_server_info(state: &AppState, info: BridgeServerInfo) -> Result<u32, String> {
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    runtime.server_info.merge(info);
+    match runtime.server_info.negotiate() {
+        Ok(version) => {
+            runtime.protocol_version = Some(version);
+            runtime.incompatibility = None;
+            Ok(version)
+        }
+        Err(message) => {
+            runtime.protocol_version = None;
+            runtime.incompatibility = Some(message.clone());
+            Err(message)
+        }
     }
 }
 
+fn bridge_supports(state: &AppState, capability: &str) -> bool {
+    state
+        .bridge_runtime
+        .lock()
+        .map(|runtime| runtime.server_info.supports(capability))
+        .unwrap_or(true)
+}
+
 fn strip_toml_comment(line: &str) -> String {
     let mut in_double_quotes = false;
     let mut in_single_quotes = false;
@@ -1165,9 +1448,7 @@ fn extract_config_value(value: Option<&Value>) -> Option<Option<String>> {
     if value.is_null() {
         return Some(None);
     }
-    let Some(text) = value.as_str() else {
-        return None;
-    };
+    let text = value.as_str()?;
     let normalized = text.trim();
     if normalized.is_empty() {
         return Some(None);
@@ -1356,47 +1637,14 @@ fn extract_effort_from_params(params: Option<&Map<String, Value>>) -> Option<Str
     )
 }
 
-fn extract_prompt_from_input(input: &Value) -> String {
-    first_text_from_input(input)
-}
-
-fn parse_image_data_urls_from_input(input: &Value) -> Vec<String> {
-    let mut urls = Vec::new();
-    let Some(items) = input.as_array() else {
-        return urls;
-    };
-
-    for entry in items {
-        let Some(obj) = entry.as_object() else {
-            continue;
-        };
-        let item_type = obj.get("type").and_then(Value::as_str).unwrap_or_default();
-        if item_type != "image" && item_type != "input_image" {
-            continue;
-        }
-        let Some(url) = obj
-            .get("url")
-            .or_else(|| obj.get("image_url"))
-            .and_then(Value::as_str)
-        else {
-            continue;
-        };
-        let normalized = url.trim();
-        if normalized.starts_with("data:image/") {
-            urls.push(normalized.to_string());
-        }
-    }
-
-    urls
-}
-
 async fn ensure_bridge_base_url(state: &AppState) -> Result<String, String> {
     let existing_base = {
         let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
         runtime.base_url.clone()
     };
     if let Some(base_url) = existing_base {
-        if bridge_health_check(&base_url).await {
+        if let Some(info) = bridge_health_check(&base_url).await {
+            record_bridge_server_info(state, info)?;
             return Ok(base_url);
         }
     }
@@ -1410,7 +1658,8 @@ async fn ensure_bridge_base_url(state: &AppState) -> Result<String, String> {
     }
 
     for candidate in candidates {
-        if bridge_health_check(&candidate).await {
+        if let Some(info) = bridge_health_check(&candidate).await {
+            record_bridge_server_info(state, info)?;
             let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
             runtime.base_url = Some(candidate.clone());
             runtime.ws_url = Some(bridge_ws_url_from_base(&candidate));
@@ -1451,13 +1700,15 @@ async fn launch_bridge_server(state: &AppState) -> Result<String, String> {
             let _ = existing.kill();
         }
         runtime.process = Some(child);
+        runtime.server_info = BridgeServerInfo::default();
         runtime.base_url = Some(base_url.clone());
         runtime.ws_url = Some(bridge_ws_url_from_base(&base_url));
     }
 
     let deadline = SystemTime::now() + Duration::from_secs(12);
     while SystemTime::now() < deadline {
-        if bridge_health_check(&base_url).await {
+        if let Some(info) = bridge_health_check(&base_url).await {
+            record_bridge_server_info(state, info)?;
             return Ok(base_url);
         }
         tokio::time::sleep(Duration::from_millis(250)).await;
@@ -1482,6 +1733,16 @@ fn bridge_endpoint(base_url: &str, path: &str) -> String {
     )
 }
 
+// Server-supplied ids go into URL paths, so anything outside `[A-Za-z0-9_-]` is escaped.
+fn encode_path_segment(value: &str) -> String {
+    utf8_percent_encode(value, SESSION_ID_ENCODE_SET).to_string()
+}
+
+fn bridge_session_path(thread_id: &str, suffix: &str) -> String {
+    let id = encode_path_segment(thread_id);
+    format!("api/v1/sessions/{id}{suffix}")
+}
+
 async fn bridge_get_json(state: &AppState, path: &str) -> Result<Value, String> {
     let base_url = ensure_bridge_base_url(state).await?;
     let url = bridge_endpoint(&base_url, path);
@@ -1526,6 +1787,32 @@ async fn bridge_post_json(state: &AppState, path: &str, body: Value) -> Result<V
         .map_err(|e| format!("Bridge POST JSON parse failed: {e}"))
 }
 
+async fn bridge_delete_json(state: &AppState, path: &str) -> Result<Value, String> {
+    let base_url = ensure_bridge_base_url(state).await?;
+    let url = bridge_endpoint(&base_url, path);
+    let response = reqwest::Client::new()
+        .delete(url)
+        .send()
+        .await
+        .map_err(|e| format!("Bridge DELETE failed: {e}"))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("Bridge DELETE failed with status {status}: {text}"));
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Bridge DELETE read failed: {e}"))?;
+    if text.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str::<Value>(&text)
+        .map_err(|e| format!("Bridge DELETE JSON parse failed: {e}"))
+}
+
 async fn ensure_bridge_ws_connected(
     app: &tauri::AppHandle,
     state: &AppState,
@@ -1574,10 +1861,20 @@ async fn ensure_bridge_ws_connected(
                 _ => continue,
             };
 
-            let Ok(envelope) = serde_json::from_str::<Value>(&text) else {
-                continue;
+            let decoded = serde_json::from_str::<Value>(&text)
+                .map_err(|e| BridgeDecodeError::InvalidEnvelope(e.to_string()))
+                .and_then(decode_bridge_event);
+            let decoded = match decoded {
+                Ok(Some(decoded)) => decoded,
+                Ok(None) => continue,
+                Err(error) => {
+                    report_bridge_diagnostic(&reader_state, &error, &text);
+                    continue;
+                }
             };
-            if let Err(error) = handle_bridge_envelope(&reader_state, envelope).await {
+            if let Err(error) =
+                handle_bridge_event(&reader_state, decoded.event, decoded.data).await
+            {
                 eprintln!("[tauri-host] bridge event handling failed: {error}");
             }
         }
@@ -1585,7 +1882,13 @@ async fn ensure_bridge_ws_connected(
         if let Ok(mut runtime) = reader_state.state::<AppState>().bridge_runtime.lock() {
             runtime.ws_sender = None;
             runtime.run_states.clear();
-            runtime.approval_run_by_request.clear();
+            runtime.pending_approvals.clear();
+            for command in runtime.pending_commands.drain(..) {
+                let _ = command.reply.send(Err(format!(
+                    "Bridge websocket closed before {} was acknowledged",
+                    command.name
+                )));
+            }
         }
     });
 
@@ -1595,47 +1898,185 @@ async fn ensure_bridge_ws_connected(
     Ok(())
 }
 
+fn bridge_data_str(data: &Value, key: &str) -> Option<String> {
+    data.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+// Sends a command and waits for the event that acknowledges it; rejections become the error.
 async fn send_bridge_command(
     app: &tauri::AppHandle,
     state: &AppState,
     name: &str,
     data: Value,
-) -> Result<(), String> {
+) -> Result<Value, String> {
     ensure_bridge_ws_connected(app, state).await?;
-    let sender = {
-        let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
-        runtime
+    let command_id = Uuid::new_v4().to_string();
+    let (reply_tx, reply_rx) = oneshot::channel();
+    let (sender, protocol_version) = {
+        let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+        if let Some(message) = &runtime.incompatibility {
+            return Err(message.clone());
+        }
+        let sender = runtime
             .ws_sender
             .clone()
-            .ok_or_else(|| "Bridge websocket sender is unavailable".to_string())?
+            .ok_or_else(|| "Bridge websocket sender is unavailable".to_string())?;
+        runtime.pending_commands.push_back(PendingBridgeCommand {
+            id: command_id.clone(),
+            name: name.to_string(),
+            session_id: bridge_data_str(&data, "sessionId"),
+            run_id: bridge_data_str(&data, "runId"),
+            request_id: bridge_data_str(&data, "requestId"),
+            reply: reply_tx,
+        });
+        (
+            sender,
+            runtime.protocol_version.unwrap_or(BRIDGE_PROTOCOL_VERSION),
+        )
     };
 
     let envelope = json!({
-        "protocolVersion": 1,
+        "protocolVersion": protocol_version,
         "type": "command",
         "name": name,
-        "id": Uuid::new_v4().to_string(),
+        "id": command_id,
         "data": data
     });
-    let payload = serde_json::to_string(&envelope)
-        .map_err(|e| format!("Serialize bridge command failed: {e}"))?;
-    sender
-        .send(payload)
-        .map_err(|_| "Bridge websocket channel is closed".to_string())
+    let sent = serde_json::to_string(&envelope)
+        .map_err(|e| format!("Serialize bridge command failed: {e}"))
+        .and_then(|payload| {
+            sender
+                .send(payload)
+                .map_err(|_| "Bridge websocket channel is closed".to_string())
+        });
+    if let Err(error) = sent {
+        forget_bridge_command(state, &command_id);
+        return Err(error);
+    }
+
+    match tokio::time::timeout(BRIDGE_COMMAND_TIMEOUT, reply_rx).await {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(_)) => Err(format!("Bridge dropped {name} before acknowledging it")),
+        Err(_) => {
+            forget_bridge_command(state, &command_id);
+            Err(format!(
+                "Bridge did not acknowledge {name} within {}s",
+                BRIDGE_COMMAND_TIMEOUT.as_secs()
+            ))
+        }
+    }
+}
+
+fn forget_bridge_command(state: &AppState, command_id: &str) {
+    if let Ok(mut runtime) = state.bridge_runtime.lock() {
+        runtime
+            .pending_commands
+            .retain(|command| command.id != command_id);
+    }
+}
+
+struct ResolvedBridgeCommand {
+    id: String,
+    name: String,
+    session_id: Option<String>,
+}
+
+// Resolves the oldest outstanding command accepted by `matches`; returns None when none is waiting.
+fn resolve_bridge_command(
+    state: &AppState,
+    client_id: Option<&str>,
+    matches: impl Fn(&PendingBridgeCommand) -> bool,
+    outcome: Result<Value, String>,
+) -> Result<Option<ResolvedBridgeCommand>, String> {
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    if runtime.is_other_client(client_id) {
+        return Ok(None);
+    }
+    let Some(index) = runtime.pending_commands.iter().position(matches) else {
+        return Ok(None);
+    };
+    let Some(command) = runtime.pending_commands.remove(index) else {
+        return Ok(None);
+    };
+    let _ = command.reply.send(outcome);
+    Ok(Some(ResolvedBridgeCommand {
+        id: command.id,
+        name: command.name,
+        session_id: command.session_id,
+    }))
+}
+
+fn record_bridge_error(state: &AppState, entry: Value) -> Result<(), String> {
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    if runtime.recent_errors.len() >= BRIDGE_ERROR_HISTORY_LIMIT {
+        runtime.recent_errors.pop_front();
+    }
+    runtime.recent_errors.push_back(entry);
+    Ok(())
+}
+
+fn bridge_diagnostics_json(state: &AppState) -> Result<Value, String> {
+    let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    Ok(json!({
+        "connected": runtime.ws_sender.is_some(),
+        "baseUrl": runtime.base_url,
+        "clientId": runtime.client_id,
+        "protocolVersion": runtime.protocol_version,
+        "serverVersion": runtime.server_info.server_version,
+        "capabilities": runtime.server_info.capabilities,
+        "incompatibility": runtime.incompatibility,
+        "pendingCommands": runtime
+            .pending_commands
+            .iter()
+            .map(|command| json!({ "id": command.id, "name": command.name }))
+            .collect::<Vec<_>>(),
+        "recentErrors": runtime.recent_errors.iter().rev().cloned().collect::<Vec<_>>()
+    }))
 }
 
 async fn bridge_list_sessions(
     state: &AppState,
+    offset: usize,
     limit: usize,
 ) -> Result<Vec<BridgeSessionSummary>, String> {
-    let clamped = limit.clamp(1, 200);
-    let value = bridge_get_json(state, &format!("api/v1/sessions?limit={clamped}")).await?;
+    let clamped = limit.clamp(1, BRIDGE_SESSION_LIST_PAGE_SIZE);
+    let value = bridge_get_json(
+        state,
+        &format!("api/v1/sessions?limit={clamped}&offset={offset}"),
+    )
+    .await?;
     serde_json::from_value::<Vec<BridgeSessionSummary>>(value)
         .map_err(|e| format!("Parse sessions list failed: {e}"))
 }
 
-fn bridge_summary_to_thread_list_json(summary: &BridgeSessionSummary) -> Value {
-    let now = now_unix_seconds();
+// The bridge has no single-session endpoint, so this pages the list until the session turns up.
+async fn bridge_find_session(state: &AppState, thread_id: &str) -> Option<BridgeSessionSummary> {
+    let mut offset = 0;
+    loop {
+        let page = bridge_list_sessions(state, offset, BRIDGE_SESSION_LIST_PAGE_SIZE)
+            .await
+            .ok()?;
+        let page_len = page.len();
+        if let Some(summary) = page.into_iter().find(|s| s.id == thread_id) {
+            return Some(summary);
+        }
+        if page_len < BRIDGE_SESSION_LIST_PAGE_SIZE {
+            return None;
+        }
+        offset += page_len;
+    }
+}
+
+fn bridge_summary_to_thread_list_json(
+    state: &AppState,
+    summary: &BridgeSessionSummary,
+    git_info: Value,
+) -> Value {
+    let (created_at, updated_at) = summary.timestamps(state);
     let preview = if summary.title.trim().is_empty() {
         summary.id.clone()
     } else {
@@ -1643,86 +2084,157 @@ fn bridge_summary_to_thread_list_json(summary: &BridgeSessionSummary) -> Value {
     };
     json!({
         "id": summary.id,
-        "createdAt": now,
-        "updatedAt": now,
+        "createdAt": created_at,
+        "updatedAt": updated_at,
         "preview": preview,
         "cwd": summary.cwd.clone().unwrap_or_else(|| "/".to_string()),
         "path": Value::Null,
-        "gitInfo": Value::Null,
+        "gitInfo": git_info,
         "source": default_thread_source()
     })
 }
 
-fn trace_entry_to_item(entry: &BridgeSessionTraceEntry) -> Option<Value> {
-    if entry.kind.eq_ignore_ascii_case("reasoning") {
-        let text = entry
-            .text
-            .as_ref()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())?;
-        let summary_text = if let Some(title) = entry
-            .title
-            .as_ref()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-        {
-            format!("{title}: {text}")
-        } else {
-            text
-        };
-        return Some(json!({
-            "id": format!("reasoning-{}", Uuid::new_v4()),
-            "type": "reasoning",
-            "summary": [summary_text]
-        }));
-    }
-
-    if entry.kind.eq_ignore_ascii_case("command") {
-        return Some(json!({
-            "id": format!("command-{}", Uuid::new_v4()),
+fn trace_entry_to_item(entry: &BridgeSessionTraceEntry, id_suffix: &str) -> Option<Value> {
+    let status = if entry.failed() {
+        "failed"
+    } else {
+        "completed"
+    };
+    match entry.render_kind()? {
+        TraceEntryKind::Reasoning => {
+            let text = entry.trimmed_text()?;
+            let summary_text = if let Some(title) = entry
+                .title
+                .as_ref()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+            {
+                format!("{title}: {text}")
+            } else {
+                text
+            };
+            Some(json!({
+                "id": format!("reasoning-{id_suffix}"),
+                "type": "reasoning",
+                "summary": [summary_text]
+            }))
+        }
+        TraceEntryKind::Command => Some(json!({
+            "id": format!("command-{id_suffix}"),
             "type": "commandExecution",
             "command": entry.command.clone().unwrap_or_default(),
             "status": entry.status.clone().unwrap_or_else(|| "completed".to_string()),
             "exitCode": entry.exit_code,
             "aggregatedOutput": entry.output
-        }));
+        })),
+        TraceEntryKind::FileChange => Some(json!({
+            "id": format!("patch-{id_suffix}"),
+            "type": "fileChange",
+            "changes": match (entry.patch.as_deref(), entry.output.as_deref()) {
+                (Some(patch), _) if patch.contains("*** Begin Patch") => {
+                    file_changes::changes_from_apply_patch(patch)
+                }
+                (_, Some(output)) => file_changes::changes_from_patch_output(output),
+                _ => Vec::new(),
+            },
+            "status": status
+        })),
+        TraceEntryKind::Plan => Some(json!({
+            "id": format!("plan-{id_suffix}"),
+            "type": "plan",
+            "text": entry
+                .trimmed_text()
+                .or_else(|| entry.title.clone())
+                .unwrap_or_else(|| "Plan updated".to_string())
+        })),
+        TraceEntryKind::WebSearch => Some(json!({
+            "id": format!("search-{id_suffix}"),
+            "type": "webSearch",
+            "query": entry
+                .trimmed_text()
+                .or_else(|| entry.command.clone())
+                .unwrap_or_default()
+        })),
+        TraceEntryKind::McpToolCall { server, tool } => {
+            let output = entry.output.clone().unwrap_or_default();
+            let (result, error) = if entry.failed() {
+                (Value::Null, json!({ "message": output }))
+            } else {
+                (
+                    json!({
+                        "content": [{ "type": "text", "text": output }],
+                        "structuredContent": Value::Null
+                    }),
+                    Value::Null,
+                )
+            };
+            Some(json!({
+                "id": format!("tool-{id_suffix}"),
+                "type": "mcpToolCall",
+                "server": server,
+                "tool": tool,
+                "status": status,
+                "arguments": Value::Null,
+                "result": result,
+                "error": error,
+                "durationMs": Value::Null
+            }))
+        }
     }
-
-    None
 }
 
-fn push_history_turn(turns: &mut Vec<Value>, items: &mut Vec<Value>) {
+// A turn is named after its first message, so its id survives paging and appended history.
+fn push_history_turn(
+    session_id: &str,
+    turns: &mut Vec<(usize, Value)>,
+    items: &mut Vec<Value>,
+    first_ordinal: &mut Option<usize>,
+) {
+    let Some(ordinal) = first_ordinal.take() else {
+        return;
+    };
     if items.is_empty() {
         return;
     }
-    turns.push(json!({
-        "id": Uuid::new_v4().to_string(),
-        "status": "completed",
-        "error": Value::Null,
-        "items": items.clone()
-    }));
-    items.clear();
+    turns.push((
+        ordinal,
+        json!({
+            "id": format!("{session_id}-turn-{ordinal}"),
+            "status": "completed",
+            "error": Value::Null,
+            "items": std::mem::take(items)
+        }),
+    ));
 }
 
-async fn bridge_thread_turns(state: &AppState, thread_id: &str) -> Result<Vec<Value>, String> {
-    let value = bridge_get_json(
-        state,
-        &format!("api/v1/sessions/{thread_id}/messages?limit=200"),
-    )
-    .await?;
-    let messages = serde_json::from_value::<Vec<BridgeSessionMessage>>(value)
-        .map_err(|e| format!("Parse session messages failed: {e}"))?;
-
+// Ids derive from the session id and each message's ordinal in the whole session, so re-reading
+// or paging a thread yields the same items and the view keeps scroll, expansion and selection.
+// Messages without an ordinal are numbered on from `ordinal_base`, the page's position in the
+// session. Each turn comes back with the ordinal of its first message.
+fn bridge_history_to_turns(
+    session_id: &str,
+    ordinal_base: usize,
+    messages: Vec<BridgeSessionMessage>,
+) -> Vec<(usize, Value)> {
     let mut turns = Vec::new();
     let mut current_items = Vec::new();
+    let mut first_ordinal = None;
 
-    for message in messages {
+    for (index, message) in messages.into_iter().enumerate() {
+        let ordinal = message.ordinal.unwrap_or(ordinal_base + index);
+        let message_id = format!("{session_id}-{ordinal}");
         let role = message.role.trim().to_lowercase();
         match role.as_str() {
             "user" => {
-                push_history_turn(&mut turns, &mut current_items);
+                push_history_turn(
+                    session_id,
+                    &mut turns,
+                    &mut current_items,
+                    &mut first_ordinal,
+                );
+                first_ordinal = Some(ordinal);
                 current_items.push(json!({
-                    "id": format!("user-{}", Uuid::new_v4()),
+                    "id": format!("user-{message_id}"),
                     "type": "userMessage",
                     "content": [
                         { "type": "text", "text": message.text }
@@ -1730,89 +2242,401 @@ async fn bridge_thread_turns(state: &AppState, thread_id: &str) -> Result<Vec<Va
                 }));
             }
             "assistant" => {
-                for trace in &message.trace {
-                    if let Some(item) = trace_entry_to_item(trace) {
+                first_ordinal.get_or_insert(ordinal);
+                for (trace_index, trace) in message.trace.iter().enumerate() {
+                    let trace_id = format!("{message_id}-{trace_index}");
+                    if let Some(item) = trace_entry_to_item(trace, &trace_id) {
                         current_items.push(item);
                     }
                 }
                 current_items.push(json!({
-                    "id": format!("assistant-{}", Uuid::new_v4()),
+                    "id": format!("assistant-{message_id}"),
                     "type": "agentMessage",
                     "text": message.text
                 }));
-                push_history_turn(&mut turns, &mut current_items);
+                push_history_turn(
+                    session_id,
+                    &mut turns,
+                    &mut current_items,
+                    &mut first_ordinal,
+                );
             }
             _ => {}
         }
     }
 
-    push_history_turn(&mut turns, &mut current_items);
-    Ok(turns)
+    push_history_turn(
+        session_id,
+        &mut turns,
+        &mut current_items,
+        &mut first_ordinal,
+    );
+    turns
 }
 
-async fn bridge_session_settings(
-    state: &AppState,
-    thread_id: &str,
-) -> (Option<String>, Option<String>) {
-    let Ok(value) = bridge_get_json(state, &format!("api/v1/sessions/{thread_id}/settings")).await
-    else {
-        let config = read_codex_config_snapshot();
-        return (config.approval_policy, config.sandbox_mode);
-    };
+fn plan_steps_json(steps: &[PlanStep]) -> Value {
+    Value::Array(
+        steps
+            .iter()
+            .map(|step| json!({ "step": step.step, "status": step.normalized_status() }))
+            .collect(),
+    )
+}
 
-    let approval_policy = value
-        .get("approvalPolicy")
-        .or_else(|| value.get(CONFIG_KEY_APPROVAL_POLICY))
-        .and_then(Value::as_str)
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty());
-    let sandbox_mode = value
-        .get("sandbox")
-        .or_else(|| value.get("sandboxMode"))
-        .or_else(|| value.get(CONFIG_KEY_SANDBOX_MODE))
-        .and_then(Value::as_str)
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty());
+// Replayed threads have no live plan notifications, so the checklist renders as a plan item.
+fn plan_item_text(explanation: Option<&str>, steps: &[PlanStep]) -> String {
+    let mut lines = Vec::new();
+    if let Some(explanation) = explanation.map(str::trim).filter(|s| !s.is_empty()) {
+        lines.push(explanation.to_string());
+        lines.push(String::new());
+    }
+    for step in steps {
+        let mark = match step.normalized_status() {
+            "completed" => "[x]",
+            "inProgress" => "[~]",
+            _ => "[ ]",
+        };
+        lines.push(format!("- {mark} {}", step.step));
+    }
+    lines.join("\n")
+}
 
-    (approval_policy, sandbox_mode)
+async fn bridge_latest_plan(state: &AppState, thread_id: &str) -> Option<TurnPlanSnapshot> {
+    if let Some(cached) = state
+        .bridge_runtime
+        .lock()
+        .ok()
+        .and_then(|runtime| runtime.latest_plans.get(thread_id).cloned())
+    {
+        return cached;
+    }
+    let snapshot = bridge_get_json(state, &bridge_session_path(thread_id, "/plan"))
+        .await
+        .ok()
+        .and_then(|value| serde_json::from_value::<TurnPlanSnapshot>(value).ok())
+        .filter(|snapshot| !snapshot.plan.is_empty());
+    if let Ok(mut runtime) = state.bridge_runtime.lock() {
+        runtime
+            .latest_plans
+            .insert(thread_id.to_string(), snapshot.clone());
+    }
+    snapshot
 }
 
-async fn bridge_read_thread(
-    state: &AppState,
-    thread_id: &str,
-    include_turns: bool,
-    requested_cwd: Option<String>,
-) -> Result<Value, String> {
-    let summaries = bridge_list_sessions(state, 200).await.unwrap_or_default();
-    let summary = summaries.into_iter().find(|s| s.id == thread_id);
-    let cwd = requested_cwd
-        .or_else(|| summary.as_ref().and_then(|s| s.cwd.clone()))
-        .unwrap_or_else(|| preferred_workspace_cwd(state));
-    let preview = summary
-        .as_ref()
+// Replayed turns carry no app-server turn ids; the plan belongs to the newest turn that called
+// `update_plan`, so its checklist replaces that turn's last plan item.
+fn attach_latest_plan(turns: &mut [Value], snapshot: &TurnPlanSnapshot) {
+    let plan_item = turns
+        .iter_mut()
+        .rev()
+        .filter_map(|turn| turn.get_mut("items").and_then(Value::as_array_mut))
+        .find_map(|items| {
+            items
+                .iter_mut()
+                .rev()
+                .find(|item| item.get("type").and_then(Value::as_str) == Some("plan"))
+        });
+    if let Some(item) = plan_item {
+        item["text"] = Value::String(plan_item_text(
+            snapshot.explanation.as_deref(),
+            &snapshot.plan,
+        ));
+    }
+}
+
+// A page of converted turns, oldest first, plus the cursor for the page before it.
+type BridgeTurnsPage = (Vec<Value>, Option<String>);
+
+// Turns keyed by the ordinal of their first message, held without gaps from the oldest page
+// fetched up to the newest message.
+#[derive(Default)]
+struct BridgeHistoryCache {
+    turns: BTreeMap<usize, Value>,
+    // Cursor for the page before the oldest cached turn; `None` once the session start is held.
+    older_before: Option<usize>,
+}
+
+impl BridgeHistoryCache {
+    // Whether a page can be served without the bridge: enough turns held, or nothing older.
+    fn covers(&self, before: Option<usize>, limit: usize) -> bool {
+        self.older_before.is_none()
+            || self.turns.range(..before.unwrap_or(usize::MAX)).count() >= limit
+    }
+
+    fn page(&self, before: Option<usize>, limit: usize) -> BridgeTurnsPage {
+        let held = self
+            .turns
+            .range(..before.unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+        let start = held.len().saturating_sub(limit);
+        let next_cursor = match held.get(start) {
+            Some((ordinal, _)) if start > 0 => Some(**ordinal),
+            _ => self.older_before,
+        };
+        (
+            held[start..]
+                .iter()
+                .map(|(_, turn)| (*turn).clone())
+                .collect(),
+            next_cursor.map(|ordinal| ordinal.to_string()),
+        )
+    }
+}
+
+// Fetches and converts the messages before `before`, or the newest ones, returning the turns
+// with the cursor for the page before them.
+async fn fetch_bridge_turns(
+    state: &AppState,
+    thread_id: &str,
+    before: Option<usize>,
+    limit: usize,
+) -> Result<(Vec<(usize, Value)>, Option<usize>), String> {
+    let mut query = format!("/messages?limit={}", limit * BRIDGE_MESSAGES_PER_TURN);
+    if let Some(before) = before {
+        query.push_str(&format!("&before={before}"));
+    }
+    let value = bridge_get_json(state, &bridge_session_path(thread_id, &query)).await?;
+    let mut messages = serde_json::from_value::<Vec<BridgeSessionMessage>>(value)
+        .map_err(|e| format!("Parse session messages failed: {e}"))?;
+
+    let has_older = messages
+        .first()
+        .and_then(|message| message.ordinal)
+        .is_some_and(|ordinal| ordinal > 0);
+    if has_older {
+        // A page can open mid-turn; leave those replies to the older page so no turn is split.
+        if let Some(first_user) = messages
+            .iter()
+            .position(|message| message.role.trim().eq_ignore_ascii_case("user"))
+        {
+            messages.drain(..first_user);
+        }
+    }
+    let older_before = messages
+        .first()
+        .and_then(|message| message.ordinal)
+        .filter(|_| has_older);
+    // An older page ends right before its cursor; the newest page of an unpaged bridge is all of it.
+    let ordinal_base = before.map_or(0, |before| before.saturating_sub(messages.len()));
+    Ok((
+        bridge_history_to_turns(thread_id, ordinal_base, messages),
+        older_before,
+    ))
+}
+
+// Pages run newest first; the cursor is the ordinal of the oldest message already handed out,
+// which the bridge keeps stable as the session grows. The session's history cache serves what
+// it holds and grows one bridge page at a time toward older messages.
+async fn bridge_thread_turns_page(
+    state: &AppState,
+    thread_id: &str,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Result<BridgeTurnsPage, String> {
+    let before = match cursor {
+        Some(cursor) => Some(
+            cursor
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid turns cursor: {cursor}"))?,
+        ),
+        None => None,
+    };
+    let limit = limit.max(1);
+
+    // Where the fetch starts when it extends the cache: the cache's oldest turn, or the newest
+    // message for a fresh cache. `None` fetches a page the cache cannot hold without a gap.
+    let extends_from = {
+        let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+        match runtime.history_cache.get(thread_id) {
+            Some(cache) if cache.covers(before, limit) => return Ok(cache.page(before, limit)),
+            Some(cache) => cache
+                .older_before
+                .filter(|oldest| before.is_none_or(|before| before >= *oldest))
+                .map(Some),
+            None => before.is_none().then_some(None),
+        }
+    };
+
+    let fetch_before = extends_from.unwrap_or(before);
+    let (turns, older_before) = fetch_bridge_turns(state, thread_id, fetch_before, limit).await?;
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    // New activity during the fetch drops the cache, so only extend one still ending there.
+    let cache_end = runtime
+        .history_cache
+        .get(thread_id)
+        .map(|cache| cache.older_before);
+    if extends_from.is_some_and(|from| cache_end == from.map(Some)) {
+        let cache = runtime
+            .history_cache
+            .entry(thread_id.to_string())
+            .or_default();
+        cache.turns.extend(turns);
+        cache.older_before = older_before;
+        return Ok(cache.page(before, limit));
+    }
+    Ok((
+        turns.into_iter().map(|(_, turn)| turn).collect(),
+        older_before.map(|ordinal| ordinal.to_string()),
+    ))
+}
+
+async fn bridge_session_settings(
+    state: &AppState,
+    thread_id: &str,
+) -> (Option<String>, Option<String>) {
+    let Ok(value) = bridge_get_json(state, &bridge_session_path(thread_id, "/settings")).await
+    else {
+        let config = read_codex_config_snapshot();
+        return (config.approval_policy, config.sandbox_mode);
+    };
+
+    let approval_policy = value
+        .get("approvalPolicy")
+        .or_else(|| value.get(CONFIG_KEY_APPROVAL_POLICY))
+        .and_then(Value::as_str)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let sandbox_mode = value
+        .get("sandbox")
+        .or_else(|| value.get("sandboxMode"))
+        .or_else(|| value.get(CONFIG_KEY_SANDBOX_MODE))
+        .and_then(Value::as_str)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+
+    (approval_policy, sandbox_mode)
+}
+
+async fn bridge_read_thread(
+    state: &AppState,
+    thread_id: &str,
+    include_turns: bool,
+    requested_cwd: Option<String>,
+) -> Result<Value, String> {
+    let summary = bridge_find_session(state, thread_id).await;
+    let cwd = requested_cwd
+        .or_else(|| summary.as_ref().and_then(|s| s.cwd.clone()))
+        .unwrap_or_else(|| preferred_workspace_cwd(state));
+    let preview = summary
+        .as_ref()
         .map(|s| s.title.clone())
         .filter(|t| !t.trim().is_empty())
         .unwrap_or_else(|| thread_id.to_string());
+    let (created_at, updated_at) = match &summary {
+        Some(summary) => summary.timestamps(state),
+        None => {
+            let now = bridge_thread_activity(state, thread_id).unwrap_or_else(now_unix_seconds);
+            (now, now)
+        }
+    };
 
-    let turns = if include_turns {
-        bridge_thread_turns(state, thread_id).await?
+    let (mut turns, older_turns_cursor) = if include_turns {
+        bridge_thread_turns_page(state, thread_id, None, BRIDGE_HISTORY_PAGE_SIZE).await?
     } else {
-        Vec::new()
+        (Vec::new(), None)
     };
+    let has_plan_item = turns.iter().any(|turn| {
+        turn.get("items")
+            .and_then(Value::as_array)
+            .is_some_and(|items| {
+                items
+                    .iter()
+                    .any(|item| item.get("type").and_then(Value::as_str) == Some("plan"))
+            })
+    });
+    if has_plan_item {
+        if let Some(snapshot) = bridge_latest_plan(state, thread_id).await {
+            attach_latest_plan(&mut turns, &snapshot);
+        }
+    }
+    let git_info = git::git_info_for_cwd(state, &cwd).await;
 
     Ok(json!({
         "id": thread_id,
-        "createdAt": now_unix_seconds(),
-        "updatedAt": now_unix_seconds(),
+        "createdAt": created_at,
+        "updatedAt": updated_at,
         "preview": preview,
         "cwd": cwd,
         "path": Value::Null,
-        "gitInfo": Value::Null,
+        "gitInfo": git_info,
         "source": default_thread_source(),
-        "turns": turns
+        "turns": turns,
+        "olderTurnsCursor": older_turns_cursor
     }))
 }
 
+// Drops every host-side reference to a deleted thread so it cannot reappear from a cache.
+fn purge_deleted_thread(host: &dyn BackendHost, state: &AppState, thread_id: &str) {
+    let unpinned = state
+        .persisted_atom_state
+        .lock()
+        .ok()
+        .and_then(|mut guard| {
+            let mut pinned = parse_pinned_thread_ids(guard.get(GLOBAL_KEY_PINNED_THREAD_IDS));
+            let before = pinned.len();
+            pinned.retain(|id| id != thread_id);
+            if pinned.len() == before {
+                return None;
+            }
+            let value = Value::Array(pinned.into_iter().map(Value::String).collect());
+            guard.insert(GLOBAL_KEY_PINNED_THREAD_IDS.to_string(), value.clone());
+            Some(value)
+        });
+    // The webview holds its own copy of persisted atoms, so push the edit like a local update.
+    if let Some(value) = unpinned {
+        let _ = host.broadcast(json!({
+            "type": "persisted-atom-updated",
+            "key": GLOBAL_KEY_PINNED_THREAD_IDS,
+            "value": value,
+            "deleted": false
+        }));
+    }
+    if let Ok(mut store) = state.thread_store.lock() {
+        store.threads.remove(thread_id);
+        store.order.retain(|id| id != thread_id);
+    }
+    if let Ok(mut runtime) = state.bridge_runtime.lock() {
+        runtime.pending_turns.remove(thread_id);
+        runtime.thread_activity.remove(thread_id);
+        runtime.latest_plans.remove(thread_id);
+        runtime.history_cache.remove(thread_id);
+        runtime.session_approval_rules.remove(thread_id);
+        runtime
+            .run_states
+            .retain(|_, run| run.thread_id != thread_id);
+    }
+    turn_snapshots::forget_thread(state, thread_id);
+}
+
+fn bridge_thread_activity(state: &AppState, thread_id: &str) -> Option<i64> {
+    let runtime = state.bridge_runtime.lock().ok()?;
+    runtime.thread_activity.get(thread_id).copied()
+}
+
+// Records activity on a bridge session and tells the view so the sidebar re-sorts.
+fn touch_bridge_thread(app: &tauri::AppHandle, state: &AppState, thread_id: &str) {
+    if thread_id.is_empty() {
+        return;
+    }
+    let updated_at = now_unix_seconds();
+    if let Ok(mut runtime) = state.bridge_runtime.lock() {
+        runtime
+            .thread_activity
+            .insert(thread_id.to_string(), updated_at);
+        runtime.history_cache.remove(thread_id);
+    }
+    let _ = emit_message_to_app(
+        app,
+        mcp_notification_payload(
+            "thread/updated",
+            json!({
+                "threadId": thread_id,
+                "updatedAt": updated_at
+            }),
+        ),
+    );
+}
+
 fn queue_pending_turn(state: &AppState, thread_id: &str, turn_id: &str) -> Result<(), String> {
     let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
     runtime
@@ -1823,6 +2647,14 @@ fn queue_pending_turn(state: &AppState, thread_id: &str, turn_id: &str) -> Resul
     Ok(())
 }
 
+fn unqueue_pending_turn(state: &AppState, thread_id: &str, turn_id: &str) -> Result<(), String> {
+    let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+    if let Some(turns) = runtime.pending_turns.get_mut(thread_id) {
+        turns.retain(|pending| pending != turn_id);
+    }
+    Ok(())
+}
+
 fn build_turn_started_notification(thread_id: &str, turn_id: &str) -> Value {
     mcp_notification_payload(
         "turn/started",
@@ -1895,30 +2727,176 @@ fn mark_item_completed(
     Ok(true)
 }
 
-async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Result<(), String> {
-    if envelope.get("type").and_then(Value::as_str) != Some("event") {
-        return Ok(());
+fn turn_diff_text(file_diffs: &BTreeMap<String, String>) -> String {
+    file_diffs
+        .iter()
+        .map(|(path, diff)| file_changes::with_diff_headers(path, diff))
+        .collect()
+}
+
+fn emit_turn_diff(app: &tauri::AppHandle, thread_id: &str, turn_id: &str, diff: String) {
+    if diff.is_empty() {
+        return;
+    }
+    let _ = emit_message_to_app(
+        app,
+        mcp_notification_payload(
+            "turn/diff/updated",
+            json!({
+                "threadId": thread_id,
+                "turnId": turn_id,
+                "diff": diff
+            }),
+        ),
+    );
+}
+
+fn bridge_thread_hint<'a>(
+    session_id: &'a Option<String>,
+    thread_id: &'a Option<String>,
+) -> Option<&'a str> {
+    session_id.as_deref().or(thread_id.as_deref())
+}
+
+fn split_reasoning_item_id(raw: &str) -> (String, i64) {
+    if let Some((base, idx)) = raw.rsplit_once("_summary_") {
+        (base.to_string(), idx.parse::<i64>().unwrap_or(0))
+    } else {
+        (raw.to_string(), 0)
     }
+}
 
-    let Some(event_name) = envelope.get("name").and_then(Value::as_str) else {
+async fn finish_bridge_run(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    run_id: &str,
+    turn_status: &str,
+    turn_error: Option<Value>,
+) -> Result<(), String> {
+    if run_id.is_empty() {
         return Ok(());
+    }
+
+    let (thread_id, turn_id, pending_items, turn_error) = {
+        let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+        let Some(run) = runtime.run_states.remove(run_id) else {
+            return Ok(());
+        };
+        // Requests of a finished run can no longer be answered, so their prompts are withdrawn.
+        let stale_requests = runtime
+            .pending_approvals
+            .iter()
+            .filter(|(_, pending)| pending.run_id == run_id)
+            .map(|(request_id, _)| request_id.clone())
+            .collect::<Vec<_>>();
+        for request_id in stale_requests {
+            if let Some(pending) = runtime.pending_approvals.remove(&request_id) {
+                approvals::withdraw_approval_prompt(
+                    app,
+                    &pending.thread_id,
+                    &request_id,
+                    "cancel",
+                    "runFinished",
+                );
+            }
+        }
+        let turn_error = turn_error.or_else(|| {
+            run.cancelled_by.as_ref().map(|source| {
+                devices::cancelled_by_error(
+                    &runtime.device_names,
+                    source.client_id.as_deref(),
+                    source.device_id.as_deref(),
+                )
+            })
+        });
+
+        let pending = run
+            .started_items
+            .iter()
+            .filter(|item_id| !run.completed_items.contains(*item_id))
+            .filter_map(|item_id| run.item_payloads.get(item_id).cloned())
+            .collect::<Vec<_>>();
+        (run.thread_id, run.turn_id, pending, turn_error)
     };
-    let data = envelope.get("data").cloned().unwrap_or(Value::Null);
+
+    if thread_id.is_empty() || turn_id.is_empty() {
+        return Ok(());
+    }
+
+    for item in pending_items {
+        let _ = emit_message_to_app(
+            app,
+            mcp_notification_payload(
+                "item/completed",
+                json!({
+                    "threadId": thread_id,
+                    "turnId": turn_id,
+                    "item": item
+                }),
+            ),
+        );
+    }
+
+    let _ = emit_message_to_app(
+        app,
+        mcp_notification_payload(
+            "turn/completed",
+            json!({
+                "threadId": thread_id,
+                "turn": {
+                    "id": turn_id,
+                    "status": turn_status,
+                    "error": turn_error
+                }
+            }),
+        ),
+    );
+    touch_bridge_thread(app, state, &thread_id);
+    spawn_turn_change_summary(app, thread_id, turn_id);
+    Ok(())
+}
+
+// The snapshot diff runs several git commands, so it follows `turn/completed` from its own task
+// instead of holding up the bridge reader.
+fn spawn_turn_change_summary(app: &tauri::AppHandle, thread_id: String, turn_id: String) {
+    let app = app.clone();
+    tokio::spawn(async move {
+        let state_handle = app.state::<AppState>();
+        let Some(change_summary) =
+            turn_snapshots::finish_turn(state_handle.inner(), &turn_id).await
+        else {
+            return;
+        };
+        if change_summary["patch"].as_str().is_none_or(str::is_empty) {
+            return;
+        }
+        let _ = emit_message_to_app(
+            &app,
+            mcp_notification_payload(
+                "turn/diff/updated",
+                json!({
+                    "threadId": thread_id,
+                    "turnId": turn_id,
+                    "diff": change_summary["patch"],
+                    "changeSummary": change_summary
+                }),
+            ),
+        );
+    });
+}
+
+async fn handle_bridge_event(
+    app: &tauri::AppHandle,
+    event: BridgeEvent,
+    data: Value,
+) -> Result<(), String> {
     let state_handle = app.state::<AppState>();
     let state = state_handle.inner();
 
-    match event_name {
-        "bridge.connected" => {}
-        "session.created" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let session_id = data
-                .get("sessionId")
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim();
+    match event {
+        BridgeEvent::SessionCreated(event) => {
+            let run_id = event.run_id.as_str();
+            let session_id = event.session_id.trim();
             if run_id.is_empty() || session_id.is_empty() {
                 return Ok(());
             }
@@ -1929,15 +2907,17 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                 run.thread_id = session_id.to_string();
             }
 
+            let cwd = preferred_workspace_cwd(state);
+            let git_info = git::git_info_for_cwd(state, &cwd).await;
             let thread_payload = json!({
                 "thread": {
                     "id": session_id,
                     "createdAt": now_unix_seconds(),
                     "updatedAt": now_unix_seconds(),
                     "preview": session_id,
-                    "cwd": preferred_workspace_cwd(state),
+                    "cwd": cwd,
                     "path": Value::Null,
-                    "gitInfo": Value::Null,
+                    "gitInfo": git_info,
                     "source": default_thread_source()
                 }
             });
@@ -1946,17 +2926,20 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                 mcp_notification_payload("thread/started", thread_payload),
             );
         }
-        "run.started" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let thread_id = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str)
+        BridgeEvent::RunStarted(event) => {
+            let run_id = event.run_id.as_str();
+            let thread_id = bridge_thread_hint(&event.session_id, &event.thread_id)
                 .unwrap_or_default()
                 .trim();
+            resolve_bridge_command(
+                state,
+                event.client_id.as_deref(),
+                |command| {
+                    command.name == "chat.send"
+                        && command.targets(Some(thread_id).filter(|id| !id.is_empty()), None, None)
+                },
+                Ok(json!({ "runId": run_id })),
+            )?;
             if run_id.is_empty() || thread_id.is_empty() {
                 return Ok(());
             }
@@ -1976,22 +2959,12 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
             };
 
             let _ = emit_message_to_app(app, build_turn_started_notification(thread_id, &turn_id));
+            touch_bridge_thread(app, state, thread_id);
         }
-        "turn.started" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let thread_id = data
-                .get("threadId")
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim();
-            let turn_id = data
-                .get("turnId")
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim();
+        BridgeEvent::TurnStarted(event) => {
+            let run_id = event.run_id.as_str();
+            let thread_id = event.thread_id.trim();
+            let turn_id = event.turn_id.trim();
             if run_id.is_empty() || thread_id.is_empty() || turn_id.is_empty() {
                 return Ok(());
             }
@@ -2012,27 +2985,15 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                     emit_message_to_app(app, build_turn_started_notification(thread_id, turn_id));
             }
         }
-        "chat.message.delta" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let item_id = data
-                .get("itemId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let delta = data
-                .get("delta")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
+        BridgeEvent::ChatMessageDelta(event) => {
+            let run_id = event.run_id.as_str();
+            let item_id = event.item_id.as_str();
+            let delta = event.delta.as_str();
             if run_id.is_empty() || item_id.is_empty() || delta.is_empty() {
                 return Ok(());
             }
 
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
+            let thread_hint = bridge_thread_hint(&event.session_id, &event.thread_id);
             let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
                 return Ok(());
             };
@@ -2076,25 +3037,18 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                 ),
             );
         }
-        "chat.message" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let role = data.get("role").and_then(Value::as_str).unwrap_or_default();
-            if run_id.is_empty() || !role.eq_ignore_ascii_case("assistant") {
+        BridgeEvent::ChatMessage(event) => {
+            let run_id = event.run_id.as_deref().unwrap_or_default();
+            if run_id.is_empty() || !event.role.eq_ignore_ascii_case("assistant") {
                 return Ok(());
             }
 
-            let text = data.get("text").and_then(Value::as_str).unwrap_or_default();
+            let text = event.text.as_str();
             if text.is_empty() {
                 return Ok(());
             }
 
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
+            let thread_hint = bridge_thread_hint(&event.session_id, &event.thread_id);
             let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
                 return Ok(());
             };
@@ -2150,36 +3104,15 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                 );
             }
         }
-        "run.reasoning.delta" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let item_id_raw = data
-                .get("itemId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let delta = data
-                .get("textDelta")
-                .or_else(|| data.get("delta"))
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if run_id.is_empty() || item_id_raw.is_empty() || delta.is_empty() {
+        // The full reasoning text arrives the same way as a delta when streaming is off.
+        BridgeEvent::RunReasoningDelta(event) | BridgeEvent::RunReasoning(event) => {
+            let run_id = event.run_id.as_str();
+            if run_id.is_empty() || event.item_id.is_empty() || event.text.is_empty() {
                 return Ok(());
             }
+            let (item_id, summary_index) = split_reasoning_item_id(&event.item_id);
 
-            let (item_id, summary_index) =
-                if let Some((base, idx)) = item_id_raw.rsplit_once("_summary_") {
-                    let parsed = idx.parse::<i64>().unwrap_or(0);
-                    (base.to_string(), parsed)
-                } else {
-                    (item_id_raw.to_string(), 0)
-                };
-
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
+            let thread_hint = bridge_thread_hint(&event.session_id, &event.thread_id);
             let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
                 return Ok(());
             };
@@ -2212,46 +3145,36 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                         "turnId": turn_id,
                         "itemId": item_id,
                         "summaryIndex": summary_index,
-                        "delta": delta
+                        "delta": event.text
                     }),
                 ),
             );
         }
-        "run.reasoning" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let item_id_raw = data
-                .get("itemId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let text = data.get("text").and_then(Value::as_str).unwrap_or_default();
-            if run_id.is_empty() || item_id_raw.is_empty() || text.is_empty() {
+        BridgeEvent::RunCommand(event) => {
+            let run_id = event.run_id.as_str();
+            let item_id = event.item_id.as_str();
+            if run_id.is_empty() || item_id.is_empty() || event.command.is_empty() {
                 return Ok(());
             }
-            let (item_id, summary_index) =
-                if let Some((base, idx)) = item_id_raw.rsplit_once("_summary_") {
-                    let parsed = idx.parse::<i64>().unwrap_or(0);
-                    (base.to_string(), parsed)
-                } else {
-                    (item_id_raw.to_string(), 0)
-                };
 
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
+            let status = event
+                .status
+                .clone()
+                .unwrap_or_else(|| "inProgress".to_string());
+            let thread_hint = bridge_thread_hint(&event.session_id, &event.thread_id);
             let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
                 return Ok(());
             };
 
             let item_payload = json!({
                 "id": item_id,
-                "type": "reasoning",
-                "summary": []
+                "type": "commandExecution",
+                "command": event.command,
+                "status": status,
+                "exitCode": event.exit_code,
+                "aggregatedOutput": event.output
             });
-            if mark_item_started(state, run_id, &item_id, item_payload.clone())? {
+            if mark_item_started(state, run_id, item_id, item_payload.clone())? {
                 let _ = emit_message_to_app(
                     app,
                     mcp_notification_payload(
@@ -2259,95 +3182,27 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                         json!({
                             "threadId": thread_id,
                             "turnId": turn_id,
-                            "item": item_payload
+                            "item": item_payload.clone()
                         }),
                     ),
                 );
             }
 
-            let _ = emit_message_to_app(
-                app,
-                mcp_notification_payload(
-                    "item/reasoning/summaryTextDelta",
-                    json!({
-                        "threadId": thread_id,
-                        "turnId": turn_id,
-                        "itemId": item_id,
-                        "summaryIndex": summary_index,
-                        "delta": text
-                    }),
-                ),
-            );
-        }
-        "run.command" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let item_id = data
-                .get("itemId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let command = data
-                .get("command")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if run_id.is_empty() || item_id.is_empty() || command.is_empty() {
-                return Ok(());
-            }
-
-            let status = data
-                .get("status")
-                .and_then(Value::as_str)
-                .unwrap_or("inProgress")
-                .to_string();
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
-            let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
-                return Ok(());
-            };
-
-            let item_payload = json!({
-                "id": item_id,
-                "type": "commandExecution",
-                "command": command,
-                "status": status,
-                "exitCode": data.get("exitCode"),
-                "aggregatedOutput": data.get("output")
-            });
-            if mark_item_started(state, run_id, item_id, item_payload.clone())? {
+            if let Some(output) = event.output.as_deref().filter(|o| !o.is_empty()) {
                 let _ = emit_message_to_app(
                     app,
                     mcp_notification_payload(
-                        "item/started",
+                        "item/commandExecution/outputDelta",
                         json!({
                             "threadId": thread_id,
                             "turnId": turn_id,
-                            "item": item_payload.clone()
+                            "itemId": item_id,
+                            "delta": output
                         }),
                     ),
                 );
             }
 
-            if let Some(output) = data.get("output").and_then(Value::as_str) {
-                if !output.is_empty() {
-                    let _ = emit_message_to_app(
-                        app,
-                        mcp_notification_payload(
-                            "item/commandExecution/outputDelta",
-                            json!({
-                                "threadId": thread_id,
-                                "turnId": turn_id,
-                                "itemId": item_id,
-                                "delta": output
-                            }),
-                        ),
-                    );
-                }
-            }
-
             let terminal = matches!(
                 status.as_str(),
                 "completed" | "failed" | "declined" | "interrupted" | "canceled" | "cancelled"
@@ -2366,27 +3221,14 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                 );
             }
         }
-        "run.command.outputDelta" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let item_id = data
-                .get("itemId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            let delta = data
-                .get("delta")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if run_id.is_empty() || item_id.is_empty() || delta.is_empty() {
+        BridgeEvent::RunCommandOutputDelta(event) => {
+            let run_id = event.run_id.as_str();
+            let item_id = event.item_id.as_str();
+            if run_id.is_empty() || item_id.is_empty() || event.delta.is_empty() {
                 return Ok(());
             }
 
-            let thread_hint = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str);
+            let thread_hint = bridge_thread_hint(&event.session_id, &event.thread_id);
             let Some((thread_id, turn_id)) = run_context(state, run_id, thread_hint)? else {
                 return Ok(());
             };
@@ -2419,127 +3261,131 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                         "threadId": thread_id,
                         "turnId": turn_id,
                         "itemId": item_id,
-                        "delta": delta
+                        "delta": event.delta
                     }),
                 ),
             );
         }
-        "approval.requested" => {
-            let request_id = data
-                .get("requestId")
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim()
-                .to_string();
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim()
-                .to_string();
-            let kind = data.get("kind").and_then(Value::as_str).unwrap_or_default();
+        BridgeEvent::ApprovalRequested(event) => {
+            let request_id = event.request_id.trim().to_string();
+            let run_id = event.run_id.as_deref().unwrap_or_default().trim();
             if request_id.is_empty() {
                 return Ok(());
             }
+            let is_file_change = event
+                .kind
+                .as_deref()
+                .is_some_and(|kind| kind.eq_ignore_ascii_case("fileChange"));
             if !run_id.is_empty() {
-                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
-                runtime
-                    .approval_run_by_request
-                    .insert(request_id.clone(), run_id);
+                let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                let run = runtime.run_states.get(run_id);
+                let command = event.command.clone().or_else(|| {
+                    let item_id = event.item_id.as_deref()?;
+                    run?.item_payloads.get(item_id)?["command"]
+                        .as_str()
+                        .map(str::to_string)
+                });
+                let thread_id = run
+                    .map(|run| run.thread_id.clone())
+                    .filter(|id| !id.is_empty())
+                    .or_else(|| event.thread_id.clone())
+                    .unwrap_or_default();
+                let pending = PendingApproval {
+                    run_id: run_id.to_string(),
+                    thread_id,
+                    file_change: is_file_change,
+                    command,
+                };
+                drop(runtime);
+
+                let cwd = event
+                    .cwd
+                    .clone()
+                    .unwrap_or_else(|| preferred_workspace_cwd(state));
+                if let Some((decision, note)) =
+                    approvals::automatic_decision(state, &pending, &cwd)?
+                {
+                    approvals::log_automatic_decision(
+                        app,
+                        state,
+                        &request_id,
+                        &pending,
+                        decision,
+                        note,
+                    )?;
+                    approvals::respond_without_prompt(app, request_id, pending, decision, None);
+                    return Ok(());
+                }
+                lock_or_err(&state.bridge_runtime, "bridge_runtime")?
+                    .pending_approvals
+                    .insert(request_id.clone(), pending);
+                approvals::schedule_approval_timeout(app, request_id.clone());
             }
 
-            let method = if kind.eq_ignore_ascii_case("fileChange") {
+            let method = if is_file_change {
                 "item/fileChange/requestApproval"
             } else {
                 "item/commandExecution/requestApproval"
             };
+            // The webview reads the request params verbatim, so forward the original payload.
             let _ = emit_message_to_app(app, mcp_request_payload(&request_id, method, data));
         }
-        "run.completed" | "run.failed" | "run.canceled" => {
-            let run_id = data
-                .get("runId")
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if run_id.is_empty() {
-                return Ok(());
-            }
-
-            let (thread_id, turn_id, pending_items, turn_status, turn_error) = {
-                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
-                let Some(run) = runtime.run_states.remove(run_id) else {
-                    return Ok(());
-                };
-
-                let pending = run
-                    .started_items
-                    .iter()
-                    .filter(|item_id| !run.completed_items.contains(*item_id))
-                    .filter_map(|item_id| run.item_payloads.get(item_id).cloned())
-                    .collect::<Vec<_>>();
-
-                let turn_status = match event_name {
-                    "run.completed" => "completed",
-                    "run.canceled" => "interrupted",
-                    _ => "failed",
-                };
-                let turn_error = if event_name == "run.failed" {
-                    data.get("message")
-                        .and_then(Value::as_str)
-                        .map(|message| json!({ "message": message }))
-                } else {
-                    None
-                };
-
-                (
-                    run.thread_id,
-                    run.turn_id,
-                    pending,
-                    turn_status.to_string(),
-                    turn_error,
-                )
-            };
-
-            if thread_id.is_empty() || turn_id.is_empty() {
-                return Ok(());
-            }
-
-            for item in pending_items {
-                let _ = emit_message_to_app(
-                    app,
-                    mcp_notification_payload(
-                        "item/completed",
-                        json!({
-                            "threadId": thread_id,
-                            "turnId": turn_id,
-                            "item": item
-                        }),
-                    ),
-                );
-            }
-
-            let _ = emit_message_to_app(
-                app,
-                mcp_notification_payload(
-                    "turn/completed",
-                    json!({
-                        "threadId": thread_id,
-                        "turn": {
-                            "id": turn_id,
-                            "status": turn_status,
-                            "error": turn_error
-                        }
-                    }),
-                ),
-            );
+        BridgeEvent::RunCompleted(event) => {
+            finish_bridge_run(app, state, &event.run_id, "completed", None).await?;
         }
-        "run.rejected" => {
-            let thread_id = data
-                .get("sessionId")
-                .or_else(|| data.get("threadId"))
-                .and_then(Value::as_str)
+        BridgeEvent::RunFailed(event) => {
+            let turn_error = event
+                .message
+                .as_deref()
+                .map(|message| json!({ "message": message }));
+            finish_bridge_run(app, state, &event.run_id, "failed", turn_error).await?;
+        }
+        BridgeEvent::RunCanceled(event) => {
+            finish_bridge_run(app, state, &event.run_id, "interrupted", None).await?;
+        }
+        BridgeEvent::RunRejected(event) => {
+            let thread_id = bridge_thread_hint(&event.session_id, &event.thread_id)
                 .unwrap_or_default()
                 .trim()
                 .to_string();
+            let reason = event.reason.as_deref().unwrap_or("Run rejected");
+            // A correlated rejection fails the originating MCP request, so no turn was ever announced.
+            // Rejections are broadcast, so only one naming this client may fail a command, and only
+            // a command of the rejected kind. Servers that omit the kind leave it to the timeout
+            // unless just one pending command could have been rejected.
+            let session_hint = Some(thread_id.as_str()).filter(|id| !id.is_empty());
+            let command_name = event.command.as_deref();
+            let matches = |command: &PendingBridgeCommand| {
+                command_name.is_none_or(|name| command.name == name)
+                    && session_hint.is_none_or(|session_id| {
+                        command
+                            .session_id
+                            .as_deref()
+                            .is_none_or(|own| own == session_id)
+                    })
+            };
+            let correlated = {
+                let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                runtime.is_own_client(event.client_id.as_deref())
+                    && (command_name.is_some()
+                        || runtime
+                            .pending_commands
+                            .iter()
+                            .filter(|command| matches(command))
+                            .count()
+                            == 1)
+            };
+            if correlated
+                && resolve_bridge_command(
+                    state,
+                    event.client_id.as_deref(),
+                    matches,
+                    Err(reason.to_string()),
+                )?
+                .is_some()
+            {
+                return Ok(());
+            }
             if thread_id.is_empty() {
                 return Ok(());
             }
@@ -2554,12 +3400,6 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                     .unwrap_or_else(|| Uuid::new_v4().to_string())
             };
 
-            let error_message = data
-                .get("reason")
-                .and_then(Value::as_str)
-                .unwrap_or("Run rejected")
-                .to_string();
-
             let _ = emit_message_to_app(
                 app,
                 mcp_notification_payload(
@@ -2569,466 +3409,280 @@ async fn handle_bridge_envelope(app: &tauri::AppHandle, envelope: Value) -> Resu
                         "turn": {
                             "id": turn_id,
                             "status": "failed",
-                            "error": { "message": error_message }
+                            "error": { "message": reason }
                         }
                     }),
                 ),
             );
         }
-        _ => {}
-    }
-
-    Ok(())
-}
-
-async fn handle_mcp_response(
-    app: &tauri::AppHandle,
-    state: &AppState,
-    message: &Value,
-) -> Result<(), String> {
-    let response = message
-        .get("response")
-        .or_else(|| message.get("message"))
-        .and_then(Value::as_object);
-    let Some(response) = response else {
-        return Ok(());
-    };
-
-    let request_id = response
-        .get("id")
-        .and_then(|value| {
-            value
-                .as_str()
-                .map(|s| s.to_string())
-                .or_else(|| value.as_i64().map(|v| v.to_string()))
-        })
-        .unwrap_or_default();
-    if request_id.trim().is_empty() {
-        return Ok(());
-    }
-
-    let result = response.get("result").and_then(Value::as_object);
-    let decision = result
-        .and_then(|obj| obj.get("decision"))
-        .and_then(Value::as_str)
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty())
-        .unwrap_or_else(|| "decline".to_string());
-
-    let run_id = {
-        let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
-        runtime.approval_run_by_request.remove(&request_id)
-    }
-    .or_else(|| {
-        result
-            .and_then(|obj| obj.get("runId"))
-            .and_then(Value::as_str)
-            .map(|v| v.trim().to_string())
-            .filter(|v| !v.is_empty())
-    });
-
-    let Some(run_id) = run_id else {
-        return Ok(());
-    };
-
-    send_bridge_command(
-        app,
-        state,
-        "approval.respond",
-        json!({
-            "runId": run_id,
-            "requestId": request_id,
-            "decision": decision
-        }),
-    )
-    .await
-}
-
-async fn handle_mcp_request(
-    app: &tauri::AppHandle,
-    window: &Window,
-    state: &AppState,
-    message: &Value,
-) -> Result<(), String> {
-    let Some(request) = message.get("request").and_then(Value::as_object) else {
-        return Ok(());
-    };
-
-    let id = request
-        .get("id")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown-mcp-id");
-    let method = request
-        .get("method")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown-method");
-    let params = request.get("params").and_then(Value::as_object);
-
-    let payload = match method {
-        "thread/list" => {
-            let archived = params
-                .and_then(|p| p.get("archived"))
-                .and_then(Value::as_bool)
-                .unwrap_or(false);
-            let limit = params
-                .and_then(|p| p.get("limit"))
-                .and_then(Value::as_u64)
-                .map(|v| v as usize)
-                .unwrap_or(100)
-                .clamp(1, 200);
-            let offset = params
-                .and_then(|p| p.get("cursor"))
-                .and_then(Value::as_str)
-                .and_then(|v| v.parse::<usize>().ok())
-                .unwrap_or(0);
-
-            if archived {
-                mcp_result_payload(id, json!({ "data": [], "nextCursor": Value::Null }))
-            } else {
-                match bridge_list_sessions(state, (limit + offset).max(limit)).await {
-                    Ok(summaries) => {
-                        let total = summaries.len();
-                        let data = summaries
-                            .into_iter()
-                            .skip(offset)
-                            .take(limit)
-                            .map(|summary| bridge_summary_to_thread_list_json(&summary))
-                            .collect::<Vec<_>>();
-                        let next_cursor = if offset + data.len() < total {
-                            Value::String((offset + data.len()).to_string())
-                        } else {
-                            Value::Null
-                        };
-                        mcp_result_payload(id, json!({ "data": data, "nextCursor": next_cursor }))
-                    }
-                    Err(error) => mcp_error_payload(id, format!("thread/list failed: {error}")),
-                }
+        BridgeEvent::BridgeConnected(event) => {
+            {
+                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                runtime.client_id = event.client_id.clone();
+            }
+            devices::refresh_device_names(app);
+            if let Err(message) = record_bridge_server_info(state, event.server) {
+                emit_bridge_diagnostic(app, "incompatible-protocol", &message, data);
+                // Dropping the sender closes the socket so no commands reach a server we cannot talk to.
+                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                runtime.ws_sender = None;
             }
         }
-        "thread/read" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "thread/read requires threadId")
+        BridgeEvent::BridgeError(event) => {
+            eprintln!("[tauri-host] bridge error: {}", event.message);
+            // The server broadcasts these for any client's unparsable message. Only one naming this
+            // client can fail a command, and the server handles messages in order, so blame the oldest.
+            let own = {
+                let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                runtime.is_own_client(event.client_id.as_deref())
+            };
+            let command = if own {
+                resolve_bridge_command(
+                    state,
+                    event.client_id.as_deref(),
+                    |_| true,
+                    Err(event.message.clone()),
+                )?
             } else {
-                let include_turns = params
-                    .and_then(|p| p.get("includeTurns"))
-                    .and_then(Value::as_bool)
-                    .unwrap_or(true);
-                let requested_cwd = params
-                    .and_then(|p| p.get("cwd"))
-                    .and_then(Value::as_str)
-                    .map(|v| v.trim().to_string())
-                    .filter(|v| !v.is_empty())
-                    .map(|v| normalize_root_string(&v));
-
-                match bridge_read_thread(state, thread_id, include_turns, requested_cwd).await {
-                    Ok(thread) => mcp_result_payload(id, json!({ "thread": thread })),
-                    Err(error) => mcp_error_payload(id, format!("thread/read failed: {error}")),
-                }
-            }
+                None
+            };
+            let thread_id = command.as_ref().and_then(|c| c.session_id.clone());
+            record_bridge_error(
+                state,
+                json!({
+                    "message": event.message,
+                    "receivedAt": now_unix_seconds(),
+                    "commandId": command.as_ref().map(|c| c.id.clone()),
+                    "command": command.as_ref().map(|c| c.name.clone()),
+                    "threadId": thread_id
+                }),
+            )?;
+            let _ = emit_message_to_app(
+                app,
+                mcp_notification_payload(
+                    "error",
+                    json!({
+                        "error": { "message": format!("Bridge error: {}", event.message) },
+                        "willRetry": false,
+                        "threadId": thread_id,
+                        "turnId": Value::Null
+                    }),
+                ),
+            );
         }
-        "thread/start" => {
-            let cwd = params
-                .and_then(|p| p.get("cwd"))
-                .and_then(Value::as_str)
-                .map(|v| v.trim().to_string())
-                .filter(|v| !v.is_empty())
-                .map(|v| normalize_root_string(&v))
-                .unwrap_or_else(|| preferred_workspace_cwd(state));
-            let config = read_codex_config_snapshot();
-            let model = extract_model_from_params(params)
-                .or_else(|| config.model.clone())
-                .unwrap_or_else(|| DEFAULT_MODEL.to_string());
-            let reasoning_effort = extract_effort_from_params(params)
-                .or_else(|| config.model_reasoning_effort.clone())
-                .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
-            let approval_policy = extract_approval_policy_from_params(params)
-                .or_else(|| config.approval_policy.clone())
-                .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
-            let sandbox_mode = extract_sandbox_from_params(params)
-                .or_else(|| config.sandbox_mode.clone())
-                .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
+        BridgeEvent::RunCancelRequested(event) => {
+            resolve_bridge_command(
+                state,
+                event.client_id.as_deref(),
+                |command| {
+                    command.name == "run.cancel"
+                        && command.targets(
+                            event.session_id.as_deref(),
+                            event.run_id.as_deref(),
+                            None,
+                        )
+                },
+                Ok(json!({ "runId": event.run_id, "sessionId": event.session_id })),
+            )?;
 
-            match bridge_post_json(state, "api/v1/sessions", json!({ "cwd": cwd })).await {
-                Ok(created) => {
-                    let thread_id = created
-                        .get("id")
-                        .and_then(Value::as_str)
-                        .unwrap_or_default()
-                        .trim()
-                        .to_string();
-                    if thread_id.is_empty() {
-                        mcp_error_payload(id, "thread/start failed: missing session id")
-                    } else {
-                        match bridge_read_thread(state, &thread_id, true, Some(cwd.clone())).await {
-                            Ok(thread_json) => mcp_result_payload(
-                                id,
-                                json!({
-                                    "thread": thread_json,
-                                    "model": model,
-                                    "reasoningEffort": reasoning_effort,
-                                    "cwd": cwd,
-                                    "sessionMeta": {
-                                        "cwd": cwd,
-                                        "approvalPolicy": approval_policy,
-                                        "sandboxMode": sandbox_mode
-                                    }
-                                }),
-                            ),
-                            Err(error) => {
-                                mcp_error_payload(id, format!("thread/start read failed: {error}"))
-                            }
-                        }
-                    }
+            let name_unknown = {
+                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                if !runtime.is_other_client(event.client_id.as_deref()) {
+                    return Ok(());
                 }
-                Err(error) => mcp_error_payload(id, format!("thread/start failed: {error}")),
-            }
-        }
-        "thread/resume" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "thread/resume requires threadId")
-            } else {
-                let config = read_codex_config_snapshot();
-                let model = extract_model_from_params(params)
-                    .or_else(|| config.model.clone())
-                    .unwrap_or_else(|| DEFAULT_MODEL.to_string());
-                let reasoning_effort = extract_effort_from_params(params)
-                    .or_else(|| config.model_reasoning_effort.clone())
-                    .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
-                let (session_approval, session_sandbox) =
-                    bridge_session_settings(state, thread_id).await;
-                let requested_cwd = params
-                    .and_then(|p| p.get("cwd"))
-                    .and_then(Value::as_str)
-                    .map(|v| v.trim().to_string())
-                    .filter(|v| !v.is_empty())
-                    .map(|v| normalize_root_string(&v));
-
-                match bridge_read_thread(state, thread_id, true, requested_cwd).await {
-                    Ok(thread_json) => {
-                        let cwd = thread_json
-                            .get("cwd")
-                            .and_then(Value::as_str)
-                            .unwrap_or("/")
-                            .to_string();
-                        mcp_result_payload(
-                            id,
-                            json!({
-                                "thread": thread_json,
-                                "model": model,
-                                "reasoningEffort": reasoning_effort,
-                                "cwd": cwd,
-                                "sessionMeta": {
-                                    "cwd": cwd,
-                                    "approvalPolicy": session_approval.unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string()),
-                                    "sandboxMode": session_sandbox.unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string())
-                                }
-                            }),
-                        )
-                    }
-                    Err(error) => mcp_error_payload(id, format!("thread/resume failed: {error}")),
+                let name_unknown = event
+                    .device_id
+                    .as_ref()
+                    .is_some_and(|device_id| !runtime.device_names.contains_key(device_id));
+                if let Some(run) = event
+                    .run_id
+                    .as_deref()
+                    .and_then(|run_id| runtime.run_states.get_mut(run_id))
+                {
+                    run.cancelled_by = Some(CancelSource {
+                        client_id: event.client_id,
+                        device_id: event.device_id,
+                    });
                 }
+                name_unknown
+            };
+            // The run usually ends well after its cancel, so the name arrives before it is shown.
+            if name_unknown {
+                devices::refresh_device_names(app);
             }
         }
-        "turn/start" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default()
-                .trim()
-                .to_string();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "turn/start requires threadId")
-            } else {
-                let input = params
-                    .and_then(|p| p.get("input"))
-                    .cloned()
-                    .unwrap_or_else(|| json!([]));
-                let prompt = extract_prompt_from_input(&input);
-                let images = parse_image_data_urls_from_input(&input);
-                if prompt.is_empty() && images.is_empty() {
-                    mcp_error_payload(id, "turn/start requires non-empty input")
-                } else {
-                    let cwd = params
-                        .and_then(|p| p.get("cwd"))
-                        .and_then(Value::as_str)
-                        .map(|v| v.trim().to_string())
-                        .filter(|v| !v.is_empty())
-                        .map(|v| normalize_root_string(&v))
-                        .unwrap_or_else(|| preferred_workspace_cwd(state));
-                    let config = read_codex_config_snapshot();
-                    let model = extract_model_from_params(params)
-                        .or_else(|| config.model.clone())
-                        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
-                    let effort = extract_effort_from_params(params)
-                        .or_else(|| config.model_reasoning_effort.clone())
-                        .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
-                    let (session_approval, session_sandbox) =
-                        bridge_session_settings(state, &thread_id).await;
-                    let approval_policy = extract_approval_policy_from_params(params)
-                        .or(session_approval)
-                        .or_else(|| config.approval_policy.clone())
-                        .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
-                    let sandbox_mode = extract_sandbox_from_params(params)
-                        .or(session_sandbox)
-                        .or_else(|| config.sandbox_mode.clone())
-                        .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
-                    let turn_id = Uuid::new_v4().to_string();
-
-                    if let Err(error) = queue_pending_turn(state, &thread_id, &turn_id) {
-                        mcp_error_payload(id, format!("turn/start queue failed: {error}"))
-                    } else {
-                        let mut command = json!({
-                            "prompt": prompt,
-                            "sessionId": thread_id,
-                            "workingDirectory": cwd,
-                            "model": model,
-                            "sandbox": sandbox_mode,
-                            "approvalPolicy": approval_policy,
-                            "effort": effort,
-                            "skipGitRepoCheck": true
-                        });
-                        if !images.is_empty() {
-                            if let Value::Object(obj) = &mut command {
-                                obj.insert(
-                                    "images".to_string(),
-                                    Value::Array(
-                                        images.into_iter().map(Value::String).collect::<Vec<_>>(),
-                                    ),
-                                );
-                            }
-                        }
+        BridgeEvent::ApprovalResponded(event) => {
+            resolve_bridge_command(
+                state,
+                event.client_id.as_deref(),
+                |command| {
+                    command.name == "approval.respond"
+                        && command.request_id.as_deref() == Some(event.request_id.as_str())
+                },
+                Ok(json!({ "requestId": event.request_id, "decision": event.decision })),
+            )?;
 
-                        match send_bridge_command(app, state, "chat.send", command).await {
-                            Ok(_) => mcp_result_payload(
-                                id,
-                                json!({
-                                    "threadId": thread_id,
-                                    "turn": {
-                                        "id": turn_id,
-                                        "status": "inProgress",
-                                        "error": Value::Null
-                                    }
-                                }),
-                            ),
-                            Err(error) => {
-                                mcp_error_payload(id, format!("turn/start failed: {error}"))
-                            }
-                        }
-                    }
+            // Another device answered first, so withdraw the prompt this window is still showing.
+            let pending = {
+                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                if !runtime.is_other_client(event.client_id.as_deref()) {
+                    return Ok(());
                 }
-            }
+                let Some(pending) = runtime.pending_approvals.remove(&event.request_id) else {
+                    return Ok(());
+                };
+                pending
+            };
+            approvals::withdraw_approval_prompt(
+                app,
+                &pending.thread_id,
+                &event.request_id,
+                event.decision.as_deref().unwrap_or_default(),
+                "resolvedElsewhere",
+            );
         }
-        "turn/interrupt" => {
-            let run_id = params.and_then(|p| p.get("runId")).and_then(Value::as_str);
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .or_else(|| params.and_then(|p| p.get("sessionId")))
-                .and_then(Value::as_str);
-            let mut data = Map::new();
-            if let Some(run_id) = run_id {
-                if !run_id.trim().is_empty() {
-                    data.insert(
-                        "runId".to_string(),
-                        Value::String(run_id.trim().to_string()),
-                    );
-                }
-            }
-            if let Some(thread_id) = thread_id {
-                if !thread_id.trim().is_empty() {
-                    data.insert(
-                        "sessionId".to_string(),
-                        Value::String(thread_id.trim().to_string()),
-                    );
-                }
-            }
-            match send_bridge_command(app, state, "run.cancel", Value::Object(data)).await {
-                Ok(_) => mcp_result_payload(id, json!({ "success": true })),
-                Err(error) => mcp_error_payload(id, format!("turn/interrupt failed: {error}")),
-            }
+        BridgeEvent::DevicePairingRequested(event) => {
+            devices::prompt_pairing_request(app, event);
         }
-        "model/list" => {
-            let config = read_codex_config_snapshot();
-            let default_model = config.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-            let default_effort = config
-                .model_reasoning_effort
-                .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
-            mcp_result_payload(
-                id,
-                json!({
-                    "data": [
-                        {
-                            "model": default_model,
-                            "isDefault": true,
-                            "defaultReasoningEffort": default_effort,
-                            "supportedReasoningEfforts": [
-                                { "reasoningEffort": "minimal", "description": "Minimal effort" },
-                                { "reasoningEffort": "low", "description": "Low effort" },
-                                { "reasoningEffort": "medium", "description": "Medium effort" },
-                                { "reasoningEffort": "high", "description": "High effort" }
-                            ]
-                        }
-                    ],
-                    "nextCursor": Value::Null
-                }),
-            )
+        BridgeEvent::DevicePresenceUpdated(event) => {
+            let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+            runtime.device_presence.insert(
+                event.device_id,
+                DevicePresence {
+                    online: event.online,
+                    last_seen_at: event.last_seen_at,
+                },
+            );
         }
-        "config/read" => {
-            let snapshot = read_codex_config_snapshot();
-            let model = snapshot.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
-            let effort = snapshot
-                .model_reasoning_effort
-                .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
-            let approval_policy = snapshot
-                .approval_policy
-                .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
-            let sandbox_mode = snapshot
-                .sandbox_mode
-                .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
-            mcp_result_payload(
-                id,
-                json!({
-                    "config": {
-                        "model": model,
-                        "model_reasoning_effort": effort,
-                        "approval_policy": approval_policy,
-                        "sandbox_mode": sandbox_mode,
-                        "modelReasoningEffort": effort,
-                        "approvalPolicy": approval_policy,
-                        "sandboxMode": sandbox_mode
-                    },
-                    "layers": []
-                }),
-            )
+        BridgeEvent::RunPlanUpdated(event) => {
+            let run_id = event.run_id.as_str();
+            if run_id.is_empty() {
+                return Ok(());
+            }
+            let Some((thread_id, turn_id)) =
+                run_context(state, run_id, event.thread_id.as_deref())?
+            else {
+                return Ok(());
+            };
+            let _ = emit_message_to_app(
+                app,
+                mcp_notification_payload(
+                    "turn/plan/updated",
+                    json!({
+                        "threadId": thread_id,
+                        "turnId": turn_id,
+                        "explanation": event.explanation,
+                        "plan": plan_steps_json(&event.plan)
+                    }),
+                ),
+            );
+            let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+            let snapshot = (!event.plan.is_empty()).then_some(TurnPlanSnapshot {
+                explanation: event.explanation,
+                plan: event.plan,
+            });
+            runtime.latest_plans.insert(thread_id, snapshot);
         }
-        "config/batchWrite" => {
-            let updates = collect_config_updates(params);
-            match write_codex_config_updates(&updates) {
-                Ok(_) => {
-                    mcp_result_payload(id, json!({ "version": now_unix_seconds().to_string() }))
+        BridgeEvent::DiffUpdated(event) => {
+            let run_id = event.run_id.as_str();
+            let files = event
+                .files
+                .iter()
+                .filter_map(|file| Some((file.path.as_str(), file.diff.as_deref()?)))
+                .filter(|(path, diff)| !path.is_empty() && !diff.trim().is_empty())
+                .collect::<Vec<_>>();
+            if run_id.is_empty() || files.is_empty() {
+                return Ok(());
+            }
+            let Some((thread_id, turn_id)) =
+                run_context(state, run_id, event.thread_id.as_deref())?
+            else {
+                return Ok(());
+            };
+
+            let (item_id, turn_diff) = {
+                let mut runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                let run = runtime.run_states.entry(run_id.to_string()).or_default();
+                for (path, diff) in &files {
+                    run.file_diffs.insert(path.to_string(), diff.to_string());
                 }
-                Err(error) => mcp_error_payload(id, format!("config/batchWrite failed: {error}")),
+                run.diff_updates += 1;
+                (
+                    format!("{run_id}-diff-{}", run.diff_updates),
+                    turn_diff_text(&run.file_diffs),
+                )
+            };
+            let item_payload = json!({
+                "id": item_id,
+                "type": "fileChange",
+                "changes": files
+                    .iter()
+                    .map(|(path, diff)| file_changes::diff_file_change(path, diff))
+                    .collect::<Vec<_>>(),
+                "status": "completed"
+            });
+            for method in ["item/started", "item/completed"] {
+                let _ = emit_message_to_app(
+                    app,
+                    mcp_notification_payload(
+                        method,
+                        json!({
+                            "threadId": thread_id,
+                            "turnId": turn_id,
+                            "item": item_payload.clone()
+                        }),
+                    ),
+                );
             }
+            mark_item_started(state, run_id, &item_id, item_payload.clone())?;
+            mark_item_completed(state, run_id, &item_id, item_payload)?;
+            emit_turn_diff(app, &thread_id, &turn_id, turn_diff);
         }
-        _ => {
-            return handle_mcp_request_legacy(app, window, state, message);
+        BridgeEvent::DiffSummary(event) => {
+            let run_id = event.run_id.as_str();
+            if run_id.is_empty() {
+                return Ok(());
+            }
+            let Some((thread_id, turn_id)) =
+                run_context(state, run_id, event.thread_id.as_deref())?
+            else {
+                return Ok(());
+            };
+            let turn_diff = {
+                let runtime = lock_or_err(&state.bridge_runtime, "bridge_runtime")?;
+                runtime
+                    .run_states
+                    .get(run_id)
+                    .map(|run| turn_diff_text(&run.file_diffs))
+                    .unwrap_or_default()
+            };
+            emit_turn_diff(app, &thread_id, &turn_id, turn_diff);
         }
+        BridgeEvent::RunActiveSnapshot(_) => {}
+    }
+
+    Ok(())
+}
+
+async fn handle_mcp_response(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    message: &Value,
+) -> Result<(), String> {
+    let response = message
+        .get("response")
+        .or_else(|| message.get("message"))
+        .and_then(Value::as_object);
+    let Some(response) = response else {
+        return Ok(());
     };
 
-    emit_message_to_window(window, payload)
+    backend::route_mcp_response(app, state, response).await?;
+    Ok(())
 }
 
-fn handle_mcp_request_legacy(
-    _app: &tauri::AppHandle,
+async fn handle_mcp_request(
+    app: &tauri::AppHandle,
     window: &Window,
     state: &AppState,
     message: &Value,
@@ -3047,7 +3701,17 @@ fn handle_mcp_request_legacy(
         .unwrap_or("unknown-method");
     let params = request.get("params").and_then(Value::as_object);
 
-    let payload = match method {
+    if let Some(payload) = backend::route_mcp_request(app, state, id, method, params).await {
+        return emit_message_to_window(window, payload);
+    }
+
+    let payload = handle_host_mcp_request(id, method, params);
+    emit_message_to_window(window, payload)
+}
+
+// Methods no backend answers; they describe host-side settings or are stubs the UI tolerates.
+fn handle_host_mcp_request(id: &str, method: &str, params: Option<&Map<String, Value>>) -> Value {
+    match method {
         // Frontend startup depends on this request to resolve auth loading state.
         "account/read" => mcp_result_payload(
             id,
@@ -3059,302 +3723,47 @@ fn handle_mcp_request_legacy(
             }),
         ),
         "account/logout" => mcp_result_payload(id, json!({})),
-        // The home screen and sidebar expect recent threads with pagination fields.
-        "thread/list" => {
-            let archived = params
-                .and_then(|p| p.get("archived"))
-                .and_then(Value::as_bool)
-                .unwrap_or(false);
-            let limit = params
-                .and_then(|p| p.get("limit"))
-                .and_then(Value::as_u64)
-                .map(|v| v as usize)
-                .unwrap_or(200);
-            let offset = params
-                .and_then(|p| p.get("cursor"))
-                .and_then(Value::as_str)
-                .and_then(|v| v.parse::<usize>().ok())
-                .unwrap_or(0);
-
-            let (data, next_cursor) = {
-                let store = lock_or_err(&state.thread_store, "thread_store")?;
-                let ordered_ids = store
-                    .order
-                    .iter()
-                    .filter_map(|thread_id| {
-                        let thread = store.threads.get(thread_id)?;
-                        if thread.archived == archived {
-                            Some(thread_id.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-
-                let data = ordered_ids
-                    .iter()
-                    .skip(offset)
-                    .take(limit)
-                    .filter_map(|thread_id| store.threads.get(thread_id))
-                    .map(HostThread::to_list_json)
-                    .collect::<Vec<_>>();
-
-                let next = if offset + data.len() < ordered_ids.len() {
-                    Value::String((offset + data.len()).to_string())
-                } else {
-                    Value::Null
-                };
-                (data, next)
-            };
-
-            mcp_result_payload(
-                id,
-                json!({
-                    "data": data,
-                    "nextCursor": next_cursor
-                }),
-            )
-        }
-        "thread/read" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "thread/read requires threadId")
-            } else {
-                let include_turns = params
-                    .and_then(|p| p.get("includeTurns"))
-                    .and_then(Value::as_bool)
-                    .unwrap_or(true);
-                let fallback_cwd = preferred_workspace_cwd(state);
-                let thread_json = {
-                    let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                    let thread = ensure_thread(&mut store, thread_id, &fallback_cwd);
-                    thread.archived = false;
-                    thread.updated_at = now_unix_seconds();
-                    if include_turns {
-                        thread.to_resume_json()
-                    } else {
-                        thread.to_list_json()
-                    }
-                };
-
-                mcp_result_payload(id, json!({ "thread": thread_json }))
-            }
-        }
-        "thread/start" => {
-            let cwd = params
-                .and_then(|p| p.get("cwd"))
-                .and_then(Value::as_str)
-                .filter(|v| !v.trim().is_empty())
-                .map(normalize_root_string)
-                .unwrap_or_else(|| preferred_workspace_cwd(state));
-            let model = params
-                .and_then(|p| p.get("model"))
-                .and_then(Value::as_str)
-                .unwrap_or("gpt-5.2-codex");
-            let reasoning_effort = params
-                .and_then(|p| p.get("effort"))
-                .and_then(Value::as_str)
-                .unwrap_or("medium");
-            let preview = params
-                .and_then(|p| p.get("input"))
-                .map(first_text_from_input)
-                .unwrap_or_default();
-
-            let thread_json = {
-                let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                let thread = create_host_thread(None, cwd.clone(), preview);
-                let thread_id = thread.id.clone();
-                let response = thread.to_resume_json();
-                store.threads.insert(thread_id.clone(), thread);
-                promote_thread_in_order(&mut store, &thread_id);
-                response
-            };
-
+        "skills/list" => mcp_result_payload(id, json!({ "data": [] })),
+        "mcpServerStatus/list" => mcp_result_payload(id, json!({ "data": [] })),
+        "collaborationMode/list" => mcp_result_payload(id, json!({ "data": [] })),
+        "config/read" => {
+            let snapshot = read_codex_config_snapshot();
+            let model = snapshot.model.unwrap_or_else(|| DEFAULT_MODEL.to_string());
+            let effort = snapshot
+                .model_reasoning_effort
+                .unwrap_or_else(|| DEFAULT_REASONING_EFFORT.to_string());
+            let approval_policy = snapshot
+                .approval_policy
+                .unwrap_or_else(|| DEFAULT_APPROVAL_POLICY.to_string());
+            let sandbox_mode = snapshot
+                .sandbox_mode
+                .unwrap_or_else(|| DEFAULT_SANDBOX_MODE.to_string());
+            // Config bootstrap paths require a config object; layers are optional.
             mcp_result_payload(
                 id,
                 json!({
-                    "thread": thread_json,
-                    "model": model,
-                    "reasoningEffort": reasoning_effort,
-                    "cwd": cwd,
-                    "sessionMeta": {
-                        "cwd": cwd
-                    }
+                    "config": {
+                        "model": model,
+                        "model_reasoning_effort": effort,
+                        "approval_policy": approval_policy,
+                        "sandbox_mode": sandbox_mode,
+                        "modelReasoningEffort": effort,
+                        "approvalPolicy": approval_policy,
+                        "sandboxMode": sandbox_mode
+                    },
+                    "layers": []
                 }),
             )
         }
-        "thread/resume" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "thread/resume requires threadId")
-            } else {
-                let requested_cwd = params
-                    .and_then(|p| p.get("cwd"))
-                    .and_then(Value::as_str)
-                    .filter(|v| !v.trim().is_empty())
-                    .map(normalize_root_string);
-                let fallback_cwd = requested_cwd
-                    .clone()
-                    .unwrap_or_else(|| preferred_workspace_cwd(state));
-                let model = params
-                    .and_then(|p| p.get("model"))
-                    .and_then(Value::as_str)
-                    .unwrap_or("gpt-5.2-codex");
-                let reasoning_effort = params
-                    .and_then(|p| p.get("effort"))
-                    .and_then(Value::as_str)
-                    .unwrap_or("medium");
-
-                let (thread_json, cwd) = {
-                    let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                    let thread = ensure_thread(&mut store, thread_id, &fallback_cwd);
-                    if let Some(cwd) = requested_cwd {
-                        thread.cwd = cwd;
-                    }
-                    thread.updated_at = now_unix_seconds();
-                    thread.archived = false;
-                    (thread.to_resume_json(), thread.cwd.clone())
-                };
-
-                mcp_result_payload(
-                    id,
-                    json!({
-                        "thread": thread_json,
-                        "model": model,
-                        "reasoningEffort": reasoning_effort,
-                        "cwd": cwd,
-                        "sessionMeta": {
-                            "cwd": cwd
-                        }
-                    }),
-                )
-            }
-        }
-        "turn/start" => {
-            let thread_id = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-                .unwrap_or_default();
-            if thread_id.is_empty() {
-                mcp_error_payload(id, "turn/start requires threadId")
-            } else {
-                let input = params
-                    .and_then(|p| p.get("input"))
-                    .cloned()
-                    .unwrap_or_else(|| json!([]));
-                let cwd = params
-                    .and_then(|p| p.get("cwd"))
-                    .and_then(Value::as_str)
-                    .filter(|v| !v.trim().is_empty())
-                    .map(normalize_root_string)
-                    .unwrap_or_else(|| preferred_workspace_cwd(state));
-                let preview = first_text_from_input(&input);
-                let turn_id = Uuid::new_v4().to_string();
-
-                {
-                    let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                    let thread = ensure_thread(&mut store, thread_id, &cwd);
-                    thread.cwd = cwd.clone();
-                    thread.updated_at = now_unix_seconds();
-                    thread.archived = false;
-                    if !preview.is_empty() {
-                        thread.preview = preview;
-                    }
-
-                    thread.turns.push(HostTurn {
-                        id: turn_id.clone(),
-                        status: "completed".to_string(),
-                        error: None,
-                        items: vec![
-                            json!({
-                                "id": format!("user-{}", Uuid::new_v4()),
-                                "type": "userMessage",
-                                "content": input
-                            }),
-                            json!({
-                                "id": format!("assistant-{}", Uuid::new_v4()),
-                                "type": "agentMessage",
-                                "text": "Tauri 兼容层已接收这条消息。"
-                            }),
-                        ],
-                    });
-                }
-
-                mcp_result_payload(
-                    id,
-                    json!({
-                        "threadId": thread_id,
-                        "turn": {
-                            "id": turn_id,
-                            "status": "completed",
-                            "error": null
-                        }
-                    }),
-                )
-            }
-        }
-        "turn/interrupt" => mcp_result_payload(id, json!({ "success": true })),
-        "thread/archive" => {
-            if let Some(thread_id) = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-            {
-                let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                if let Some(thread) = store.threads.get_mut(thread_id) {
-                    thread.archived = true;
-                    thread.updated_at = now_unix_seconds();
-                }
-            }
-            mcp_result_payload(id, json!({}))
-        }
-        "thread/unarchive" => {
-            if let Some(thread_id) = params
-                .and_then(|p| p.get("threadId"))
-                .and_then(Value::as_str)
-            {
-                let mut store = lock_or_err(&state.thread_store, "thread_store")?;
-                if let Some(thread) = store.threads.get_mut(thread_id) {
-                    thread.archived = false;
-                    thread.updated_at = now_unix_seconds();
-                    promote_thread_in_order(&mut store, thread_id);
+        "config/batchWrite" => {
+            let updates = collect_config_updates(params);
+            match write_codex_config_updates(&updates) {
+                Ok(_) => {
+                    mcp_result_payload(id, json!({ "version": now_unix_seconds().to_string() }))
                 }
+                Err(error) => mcp_error_payload(id, format!("config/batchWrite failed: {error}")),
             }
-            mcp_result_payload(id, json!({}))
         }
-        "model/list" => mcp_result_payload(
-            id,
-            json!({
-                "data": [
-                    {
-                        "model": "gpt-5.2-codex",
-                        "isDefault": true,
-                        "defaultReasoningEffort": "medium",
-                        "supportedReasoningEfforts": [
-                            { "reasoningEffort": "minimal", "description": "Minimal effort" },
-                            { "reasoningEffort": "low", "description": "Low effort" },
-                            { "reasoningEffort": "medium", "description": "Medium effort" },
-                            { "reasoningEffort": "high", "description": "High effort" }
-                        ]
-                    }
-                ],
-                "nextCursor": null
-            }),
-        ),
-        "skills/list" => mcp_result_payload(id, json!({ "data": [] })),
-        "mcpServerStatus/list" => mcp_result_payload(id, json!({ "data": [] })),
-        "collaborationMode/list" => mcp_result_payload(id, json!({ "data": [] })),
-        "config/batchWrite" => mcp_result_payload(
-            id,
-            json!({
-                "version": now_unix_seconds().to_string()
-            }),
-        ),
         "feedback/upload" => mcp_result_payload(
             id,
             json!({
@@ -3369,14 +3778,6 @@ fn handle_mcp_request_legacy(
                 "nextCursor": null
             }),
         ),
-        // Config bootstrap paths require a config object; layers are optional.
-        "config/read" => mcp_result_payload(
-            id,
-            json!({
-                "config": {},
-                "layers": []
-            }),
-        ),
         "configRequirements/read" => mcp_result_payload(
             id,
             json!({
@@ -3387,9 +3788,7 @@ fn handle_mcp_request_legacy(
             println!("[tauri-host] unimplemented mcp method: {method}; returning empty result");
             mcp_result_payload(id, json!({}))
         }
-    };
-
-    emit_message_to_window(window, payload)
+    }
 }
 
 fn emit_fetch_json_success(window: &Window, request_id: &str, body: Value) -> Result<(), String> {
@@ -3400,7 +3799,7 @@ fn emit_fetch_json_success(window: &Window, request_id: &str, body: Value) -> Re
     )
 }
 
-fn handle_vscode_fetch(
+async fn handle_vscode_fetch(
     app: &tauri::AppHandle,
     window: &Window,
     state: &AppState,
@@ -3458,9 +3857,7 @@ fn handle_vscode_fetch(
                     if let Some(label) = custom_label {
                         workspace.labels.insert(root.clone(), label);
                     }
-                    if set_active {
-                        workspace.active_roots = vec![root.clone()];
-                    } else if workspace.active_roots.is_empty() {
+                    if set_active || workspace.active_roots.is_empty() {
                         workspace.active_roots = vec![root.clone()];
                     }
                     workspace.clone()
@@ -3516,9 +3913,42 @@ fn handle_vscode_fetch(
         "codex-home" => json!({
             "codexHome": default_codex_home_path()
         }),
-        "git-origins" => json!({
-            "origins": []
-        }),
+        "git-origins" => {
+            let dirs = git::request_dirs(state, Some(&body))?;
+            git::git_origins(&dirs).await
+        }
+        "git-status" | "git-current-branch" | "git-diff" => {
+            let cwd = params
+                .and_then(|p| p.get("root").or_else(|| p.get("cwd")))
+                .and_then(Value::as_str)
+                .filter(|v| !v.trim().is_empty())
+                .map(normalize_root_string)
+                .unwrap_or_else(|| preferred_workspace_cwd(state));
+            let result = match endpoint {
+                "git-status" => git::git_status(&cwd).await,
+                "git-current-branch" => git::git_branch_info(&cwd).await,
+                _ => {
+                    let paths = params
+                        .and_then(|p| p.get("paths"))
+                        .and_then(Value::as_array)
+                        .map(|items| {
+                            items
+                                .iter()
+                                .filter_map(Value::as_str)
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    git::git_diff(&cwd, &paths, |_| {})
+                        .await
+                        .map(|files| json!({ "files": files }))
+                }
+            };
+            match result {
+                Ok(value) => value,
+                Err(error) => return emit_fetch_error(window, request_id, error),
+            }
+        }
         "paths-exist" => {
             let candidate_paths = params
                 .and_then(|p| p.get("paths").or_else(|| p.get("dirs")))
@@ -3629,15 +4059,45 @@ fn handle_vscode_fetch(
             "email": null,
             "accountId": null
         }),
-        "open-in-targets" => json!({ "targets": [] }),
+        "open-in-targets" => match open_targets::list_open_targets(state) {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
         "extension-info" => json!({
             "windowType": "electron",
             "host": "tauri"
         }),
-        "open-file" => json!({ "success": true }),
-        "pick-files" => json!({ "paths": [] }),
-        "find-files" => json!({ "files": [] }),
-        "set-preferred-app" => json!({ "success": true }),
+        "open-file" => match open_targets::open_file(state, params) {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "pick-files" => file_search::pick_files(window, params).await,
+        "find-files" => match file_search::find_files(state, request_id, params, |_| {}).await {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "set-preferred-app" => match open_targets::set_preferred_target(state, params) {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "bridge-diagnostics" => bridge_diagnostics_json(state)?,
+        "bridge-devices" => match devices::list_devices_or_presence(state).await {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "revoke-bridge-device" => match devices::revoke_device(state, params).await {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "approval-rules" => approval_rules::list_rules(state)?,
+        "save-approval-rule" => match approval_rules::save_rule(state, params) {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
+        "delete-approval-rule" => match approval_rules::delete_rule(state, params) {
+            Ok(value) => value,
+            Err(error) => return emit_fetch_error(window, request_id, error),
+        },
         "get-configuration" => json!({}),
         "set-configuration" => json!({ "success": true }),
         other => {
@@ -3669,19 +4129,19 @@ async fn handle_fetch(
 
     let Some(url) = message.get("url").and_then(Value::as_str) else {
         return emit_message_to_window(
-            &window,
+            window,
             json_fetch_error(&request_id, 400, "Missing fetch url"),
         );
     };
 
     if let Some(endpoint) = parse_vscode_endpoint(url) {
-        return handle_vscode_fetch(app, window, state, &request_id, &endpoint, message);
+        return handle_vscode_fetch(app, window, state, &request_id, &endpoint, message).await;
     }
 
     let absolute_url = ensure_absolute_url(url);
     if absolute_url.starts_with("data:") {
         return emit_message_to_window(
-            &window,
+            window,
             json_fetch_error(
                 &request_id,
                 400,
@@ -3704,7 +4164,7 @@ async fn handle_fetch(
         Ok(resp) => resp,
         Err(e) => {
             return emit_message_to_window(
-                &window,
+                window,
                 json_fetch_error(&request_id, 500, format!("Fetch failed: {e}")),
             )
         }
@@ -3720,7 +4180,7 @@ async fn handle_fetch(
             .ok()
             .filter(|t| !t.trim().is_empty())
             .unwrap_or_else(|| format!("Request failed with status {status}"));
-        return emit_message_to_window(&window, json_fetch_error(&request_id, status, message));
+        return emit_message_to_window(window, json_fetch_error(&request_id, status, message));
     }
 
     let content_type = response
@@ -3732,7 +4192,7 @@ async fn handle_fetch(
 
     if status == 204 {
         return emit_message_to_window(
-            &window,
+            window,
             json_fetch_success(&request_id, status, headers_json, "null".to_string()),
         );
     }
@@ -3742,7 +4202,7 @@ async fn handle_fetch(
             Ok(v) => v,
             Err(e) => {
                 return emit_message_to_window(
-                    &window,
+                    window,
                     json_fetch_error(&request_id, 500, format!("Read JSON response failed: {e}")),
                 )
             }
@@ -3754,7 +4214,7 @@ async fn handle_fetch(
         };
 
         return emit_message_to_window(
-            &window,
+            window,
             json_fetch_success(&request_id, status, headers_json, body_json_string),
         );
     }
@@ -3763,7 +4223,7 @@ async fn handle_fetch(
         Ok(v) => v,
         Err(e) => {
             return emit_message_to_window(
-                &window,
+                window,
                 json_fetch_error(&request_id, 500, format!("Read response bytes failed: {e}")),
             )
         }
@@ -3776,7 +4236,7 @@ async fn handle_fetch(
     .unwrap_or_else(|_| "{}".to_string());
 
     emit_message_to_window(
-        &window,
+        window,
         json_fetch_success(&request_id, status, headers_json, body_json_string),
     )
 }
@@ -3949,7 +4409,12 @@ async fn handle_message(
                 }),
             )?;
         }
-        "cancel-fetch" | "cancel-fetch-stream" => {}
+        "cancel-fetch" => {
+            if let Some(request_id) = message.get("requestId").and_then(Value::as_str) {
+                file_search::cancel_find_files(state, request_id)?;
+            }
+        }
+        "cancel-fetch-stream" => {}
         "mcp-request" => {
             handle_mcp_request(app, window, state, &message).await?;
         }
@@ -4114,50 +4579,22 @@ async fn send_message_from_view(
 
 #[tauri::command]
 async fn send_worker_message_from_view(
+    app: tauri::AppHandle,
+    window: Window,
     worker_id: String,
     message: Value,
-    window: Window,
 ) -> Result<(), String> {
-    match message_type(&message).unwrap_or_default() {
-        "worker-request" => {
-            let request = message.get("request").cloned().unwrap_or_else(|| json!({}));
-            let id = request.get("id").cloned().unwrap_or(Value::Null);
-            let method = request
-                .get("method")
-                .cloned()
-                .unwrap_or_else(|| Value::String("unknown".to_string()));
-
-            emit_worker_to_window(
-                &window,
-                &worker_id,
-                json!({
-                    "type": "worker-response",
-                    "workerId": worker_id,
-                    "response": {
-                        "id": id,
-                        "method": method,
-                        "result": {
-                            "type": "error",
-                            "error": {
-                                "message": "Tauri worker bridge is not implemented for this worker yet."
-                            }
-                        }
-                    }
-                }),
-            )?;
-        }
-        "worker-request-cancel" => {}
-        other => {
-            println!("[tauri-worker] unhandled worker message: {other}");
-        }
-    }
-
-    Ok(())
+    workers::handle_worker_message(&app, &window, &worker_id, &message)
 }
 
 #[tauri::command]
-async fn show_context_menu(_items: Value) -> Result<ContextMenuResult, String> {
-    Ok(ContextMenuResult { id: None })
+async fn show_context_menu(
+    window: Window,
+    state: State<'_, AppState>,
+    items: Value,
+) -> Result<ContextMenuResult, String> {
+    let id = context_menu::show_context_menu(&window, state.inner(), &items).await?;
+    Ok(ContextMenuResult { id })
 }
 
 #[tauri::command]
@@ -4175,6 +4612,7 @@ fn get_bridge_meta(state: State<'_, AppState>) -> BridgeMeta {
 pub fn run() {
     tauri::Builder::default()
         .manage(AppState::new())
+        .on_menu_event(context_menu::handle_menu_event)
         .invoke_handler(tauri::generate_handler![
             send_message_from_view,
             send_worker_message_from_view,
@@ -4182,6 +4620,94 @@ pub fn run() {
             trigger_sentry_test_error,
             get_bridge_meta
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app_server::shutdown(app.state::<AppState>().inner());
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached_history(ordinals: &[usize], older_before: Option<usize>) -> BridgeHistoryCache {
+        BridgeHistoryCache {
+            turns: ordinals
+                .iter()
+                .map(|ordinal| (*ordinal, json!({ "id": format!("s-turn-{ordinal}") })))
+                .collect(),
+            older_before,
+        }
+    }
+
+    fn turn_ids(page: &BridgeTurnsPage) -> Vec<&str> {
+        page.0
+            .iter()
+            .map(|turn| turn["id"].as_str().unwrap_or_default())
+            .collect()
+    }
+
+    fn message(ordinal: Option<usize>, role: &str, text: &str) -> BridgeSessionMessage {
+        serde_json::from_value(json!({ "ordinal": ordinal, "role": role, "text": text }))
+            .expect("message")
+    }
+
+    #[test]
+    fn replayed_ids_follow_message_ordinals_or_the_page_base() {
+        let with_ordinals = bridge_history_to_turns(
+            "s",
+            0,
+            vec![
+                message(Some(6), "user", "hi"),
+                message(Some(7), "assistant", "hello"),
+            ],
+        );
+        assert_eq!(with_ordinals.len(), 1);
+        assert_eq!(with_ordinals[0].0, 6);
+        assert_eq!(with_ordinals[0].1["id"], "s-turn-6");
+        assert_eq!(with_ordinals[0].1["items"][0]["id"], "user-s-6");
+        assert_eq!(with_ordinals[0].1["items"][1]["id"], "assistant-s-7");
+
+        let without_ordinals = bridge_history_to_turns(
+            "s",
+            10,
+            vec![
+                message(None, "user", "again"),
+                message(None, "assistant", "sure"),
+            ],
+        );
+        assert_eq!(without_ordinals[0].0, 10);
+        assert_eq!(without_ordinals[0].1["items"][1]["id"], "assistant-s-11");
+    }
+
+    #[test]
+    fn history_cache_pages_newest_first_with_ordinal_cursors() {
+        let cache = cached_history(&[4, 6, 8, 10], Some(4));
+
+        let newest = cache.page(None, 2);
+        assert_eq!(turn_ids(&newest), ["s-turn-8", "s-turn-10"]);
+        assert_eq!(newest.1.as_deref(), Some("8"));
+
+        let older = cache.page(Some(8), 2);
+        assert_eq!(turn_ids(&older), ["s-turn-4", "s-turn-6"]);
+        assert_eq!(older.1.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn history_cache_falls_back_to_the_bridge_past_what_it_holds() {
+        let partial = cached_history(&[4, 6, 8, 10], Some(4));
+        assert!(partial.covers(None, 4));
+        assert!(partial.covers(Some(8), 2));
+        assert!(!partial.covers(Some(8), 3));
+        assert!(!partial.covers(Some(4), 1));
+
+        let complete = cached_history(&[0, 2], None);
+        assert!(complete.covers(None, 50));
+        let page = complete.page(None, 50);
+        assert_eq!(turn_ids(&page), ["s-turn-0", "s-turn-2"]);
+        assert_eq!(page.1, None);
+    }
 }
@@ -0,0 +1,612 @@
+// This module detects editors that can open files and launches them at a line and column.
+// It backs the open-in-targets, open-file and set-preferred-app vscode://codex/ endpoints.
+
+use serde_json::{json, Map, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use url::Url;
+
+use crate::{local_app_data_dir, lock_or_err, preferred_workspace_cwd, resolve_home_dir, AppState};
+
+const SYSTEM_TARGET_ID: &str = "system";
+const EDITOR_ENV_TARGET_ID: &str = "editor";
+const TARGET_CACHE_TTL: Duration = Duration::from_secs(60);
+const PREFERENCES_FILE_NAME: &str = "open_target_preferences.json";
+
+// (id, label, launcher names) for editors that accept `--goto path:line:column`.
+const VSCODE_LIKE_EDITORS: &[(&str, &str, &[&str])] = &[
+    ("vscode", "VS Code", &["code"]),
+    ("vscode-insiders", "VS Code Insiders", &["code-insiders"]),
+    ("cursor", "Cursor", &["cursor"]),
+    ("windsurf", "Windsurf", &["windsurf"]),
+];
+
+// JetBrains launchers accept `--line N --column N path`.
+const JETBRAINS_EDITORS: &[(&str, &str, &[&str])] = &[
+    ("intellij", "IntelliJ IDEA", &["idea", "idea64"]),
+    ("pycharm", "PyCharm", &["pycharm", "pycharm64"]),
+    ("webstorm", "WebStorm", &["webstorm", "webstorm64"]),
+    ("goland", "GoLand", &["goland", "goland64"]),
+    ("clion", "CLion", &["clion", "clion64"]),
+    ("rider", "Rider", &["rider", "rider64"]),
+    ("rustrover", "RustRover", &["rustrover", "rustrover64"]),
+    ("phpstorm", "PhpStorm", &["phpstorm", "phpstorm64"]),
+    ("rubymine", "RubyMine", &["rubymine", "rubymine64"]),
+];
+
+const TERMINAL_EDITORS: &[&str] = &["vi", "vim", "nvim", "nano", "micro", "hx", "helix", "kak"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OpenTargetKind {
+    VsCodeLike,
+    JetBrains,
+    EditorEnv,
+    SystemDefault,
+}
+
+impl OpenTargetKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            OpenTargetKind::VsCodeLike => "vscode",
+            OpenTargetKind::JetBrains => "jetbrains",
+            OpenTargetKind::EditorEnv => "editor",
+            OpenTargetKind::SystemDefault => "system",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct OpenTarget {
+    id: String,
+    label: String,
+    kind: OpenTargetKind,
+    program: PathBuf,
+    base_args: Vec<String>,
+    // Terminal editors from $EDITOR need a terminal emulator to be visible.
+    terminal: Option<PathBuf>,
+}
+
+impl OpenTarget {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "label": self.label,
+            "kind": self.kind.as_str(),
+            "path": if self.kind == OpenTargetKind::SystemDefault {
+                Value::Null
+            } else {
+                Value::String(self.program.to_string_lossy().to_string())
+            }
+        })
+    }
+}
+
+pub(crate) struct OpenTargetState {
+    targets: Mutex<Option<(Instant, Vec<OpenTarget>)>>,
+    preferred: Mutex<Option<String>>,
+}
+
+impl Default for OpenTargetState {
+    fn default() -> Self {
+        Self {
+            targets: Mutex::new(None),
+            preferred: Mutex::new(read_preferred_target()),
+        }
+    }
+}
+
+fn preferences_path() -> Option<PathBuf> {
+    Some(
+        local_app_data_dir()?
+            .join("codex-relayouter")
+            .join(PREFERENCES_FILE_NAME),
+    )
+}
+
+fn read_preferred_target() -> Option<String> {
+    let content = fs::read_to_string(preferences_path()?).ok()?;
+    let json: Value = serde_json::from_str(&content).ok()?;
+    json.get("preferredTarget")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn write_preferred_target(target: Option<&str>) -> Result<(), String> {
+    let path =
+        preferences_path().ok_or_else(|| "Could not resolve preferences directory".to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create preferences directory: {e}"))?;
+    }
+    let body = serde_json::to_string_pretty(&json!({ "preferredTarget": target }))
+        .map_err(|e| format!("Failed to serialize open target preferences: {e}"))?;
+    fs::write(&path, body).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn executable_names(name: &str) -> Vec<String> {
+    if cfg!(target_os = "windows") {
+        vec![
+            format!("{name}.exe"),
+            format!("{name}.cmd"),
+            format!("{name}.bat"),
+        ]
+    } else {
+        vec![name.to_string()]
+    }
+}
+
+// Editors installed per-user are often missing from the PATH a GUI app inherits.
+fn extra_search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let home = resolve_home_dir();
+    if cfg!(target_os = "windows") {
+        if let Some(local) = local_app_data_dir() {
+            dirs.push(local.join("Programs").join("Microsoft VS Code").join("bin"));
+            dirs.push(
+                local
+                    .join("Programs")
+                    .join("Microsoft VS Code Insiders")
+                    .join("bin"),
+            );
+            dirs.push(
+                local
+                    .join("Programs")
+                    .join("cursor")
+                    .join("resources")
+                    .join("app")
+                    .join("bin"),
+            );
+            dirs.push(local.join("Programs").join("Windsurf").join("bin"));
+            dirs.push(local.join("JetBrains").join("Toolbox").join("scripts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        for app in [
+            "Visual Studio Code",
+            "Visual Studio Code - Insiders",
+            "Cursor",
+            "Windsurf",
+        ] {
+            dirs.push(
+                Path::new("/Applications")
+                    .join(format!("{app}.app"))
+                    .join("Contents")
+                    .join("Resources")
+                    .join("app")
+                    .join("bin"),
+            );
+        }
+        if let Some(home) = home.as_ref() {
+            dirs.push(
+                home.join("Library")
+                    .join("Application Support")
+                    .join("JetBrains")
+                    .join("Toolbox")
+                    .join("scripts"),
+            );
+        }
+        dirs.push(PathBuf::from("/usr/local/bin"));
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+    } else if let Some(home) = home.as_ref() {
+        dirs.push(
+            home.join(".local")
+                .join("share")
+                .join("JetBrains")
+                .join("Toolbox")
+                .join("scripts"),
+        );
+        dirs.push(home.join(".local").join("bin"));
+    }
+    dirs
+}
+
+pub(crate) fn find_executable(name: &str) -> Option<PathBuf> {
+    let candidate = Path::new(name);
+    if candidate.is_absolute() {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    let mut dirs = env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    dirs.extend(extra_search_dirs());
+
+    dirs.iter().find_map(|dir| {
+        executable_names(name)
+            .into_iter()
+            .map(|file_name| dir.join(file_name))
+            .find(|path| path.is_file())
+    })
+}
+
+fn find_terminal_emulator() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    [
+        "x-terminal-emulator",
+        "xterm",
+        "konsole",
+        "alacritty",
+        "kitty",
+    ]
+    .into_iter()
+    .find_map(find_executable)
+}
+
+fn editor_env_target() -> Option<OpenTarget> {
+    let raw = env::var("VISUAL")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|value| !value.trim().is_empty())?;
+    let mut parts = raw.split_whitespace();
+    let program = find_executable(parts.next()?)?;
+    let base_args = parts.map(str::to_string).collect::<Vec<_>>();
+
+    let name = editor_program_name(&program);
+    let terminal = if TERMINAL_EDITORS.contains(&name.as_str()) {
+        Some(find_terminal_emulator()?)
+    } else {
+        None
+    };
+
+    Some(OpenTarget {
+        id: EDITOR_ENV_TARGET_ID.to_string(),
+        label: format!("$EDITOR ({name})"),
+        kind: OpenTargetKind::EditorEnv,
+        program,
+        base_args,
+        terminal,
+    })
+}
+
+fn editor_program_name(program: &Path) -> String {
+    program
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn detect_open_targets() -> Vec<OpenTarget> {
+    let mut targets = Vec::new();
+
+    for (kind, editors) in [
+        (OpenTargetKind::VsCodeLike, VSCODE_LIKE_EDITORS),
+        (OpenTargetKind::JetBrains, JETBRAINS_EDITORS),
+    ] {
+        for (id, label, launchers) in editors {
+            if let Some(program) = launchers.iter().find_map(|name| find_executable(name)) {
+                targets.push(OpenTarget {
+                    id: id.to_string(),
+                    label: label.to_string(),
+                    kind,
+                    program,
+                    base_args: Vec::new(),
+                    terminal: None,
+                });
+            }
+        }
+    }
+
+    if let Some(target) = editor_env_target() {
+        targets.push(target);
+    }
+
+    targets.push(OpenTarget {
+        id: SYSTEM_TARGET_ID.to_string(),
+        label: "System default".to_string(),
+        kind: OpenTargetKind::SystemDefault,
+        program: PathBuf::new(),
+        base_args: Vec::new(),
+        terminal: None,
+    });
+    targets
+}
+
+fn open_targets(state: &AppState) -> Result<Vec<OpenTarget>, String> {
+    let mut cached = lock_or_err(&state.open_targets.targets, "open_targets")?;
+    if let Some((detected_at, targets)) = cached.as_ref() {
+        if detected_at.elapsed() <= TARGET_CACHE_TTL {
+            return Ok(targets.clone());
+        }
+    }
+    let targets = detect_open_targets();
+    *cached = Some((Instant::now(), targets.clone()));
+    Ok(targets)
+}
+
+fn preferred_target(state: &AppState) -> Result<Option<String>, String> {
+    Ok(lock_or_err(&state.open_targets.preferred, "open_target_preferred")?.clone())
+}
+
+pub(crate) fn list_open_targets(state: &AppState) -> Result<Value, String> {
+    let targets = open_targets(state)?;
+    let preferred =
+        preferred_target(state)?.filter(|id| targets.iter().any(|target| &target.id == id));
+    Ok(json!({
+        "targets": targets.iter().map(OpenTarget::to_json).collect::<Vec<_>>(),
+        "preferredTarget": preferred
+    }))
+}
+
+fn param_str<'a>(params: Option<&'a Map<String, Value>>, keys: &[&str]) -> Option<&'a str> {
+    let params = params?;
+    keys.iter()
+        .find_map(|key| params.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn param_u64(params: Option<&Map<String, Value>>, keys: &[&str]) -> Option<u64> {
+    let params = params?;
+    keys.iter().find_map(|key| {
+        let value = params.get(*key)?;
+        value
+            .as_u64()
+            .or_else(|| value.as_str().and_then(|text| text.trim().parse().ok()))
+    })
+}
+
+pub(crate) fn set_preferred_target(
+    state: &AppState,
+    params: Option<&Map<String, Value>>,
+) -> Result<Value, String> {
+    let requested = param_str(params, &["target", "targetId", "appId", "app", "id"]);
+    if let Some(id) = requested {
+        let targets = open_targets(state)?;
+        if !targets.iter().any(|target| target.id == id) {
+            return Err(format!("Unknown open target: {id}"));
+        }
+    }
+
+    write_preferred_target(requested)?;
+    *lock_or_err(&state.open_targets.preferred, "open_target_preferred")? =
+        requested.map(str::to_string);
+    Ok(json!({ "success": true, "preferredTarget": requested }))
+}
+
+// Agent messages link paths as `src/lib.rs:42:7`; split that suffix when the path is not a file.
+fn split_location_suffix(path: &str) -> (String, Option<u64>, Option<u64>) {
+    if Path::new(path).exists() {
+        return (path.to_string(), None, None);
+    }
+    let mut rest = path;
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        let Some((head, tail)) = rest.rsplit_once(':') else {
+            break;
+        };
+        let Ok(number) = tail.parse::<u64>() else {
+            break;
+        };
+        numbers.insert(0, number);
+        rest = head;
+    }
+    (
+        rest.to_string(),
+        numbers.first().copied(),
+        numbers.get(1).copied(),
+    )
+}
+
+fn resolve_open_path(
+    state: &AppState,
+    params: Option<&Map<String, Value>>,
+) -> Result<(PathBuf, Option<u64>, Option<u64>), String> {
+    let raw = param_str(params, &["path", "file", "filePath", "uri"])
+        .ok_or_else(|| "open-file missing path".to_string())?;
+    let raw = match Url::parse(raw) {
+        Ok(url) if url.scheme() == "file" => url
+            .to_file_path()
+            .map_err(|_| format!("Invalid file url: {raw}"))?
+            .to_string_lossy()
+            .to_string(),
+        _ => raw.to_string(),
+    };
+
+    let (path, suffix_line, suffix_column) = split_location_suffix(&raw);
+    let mut path = PathBuf::from(path);
+    if path.is_relative() {
+        let cwd = param_str(params, &["cwd", "root"])
+            .map(str::to_string)
+            .unwrap_or_else(|| preferred_workspace_cwd(state));
+        path = Path::new(&cwd).join(path);
+    }
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    let line = param_u64(params, &["line", "lineNumber"])
+        .or(suffix_line)
+        .filter(|line| *line > 0);
+    let column = param_u64(params, &["column", "col"])
+        .or(suffix_column)
+        .filter(|column| *column > 0);
+    Ok((path, line, column))
+}
+
+fn editor_env_args(name: &str, path: &str, line: Option<u64>, column: Option<u64>) -> Vec<String> {
+    let Some(line) = line else {
+        return vec![path.to_string()];
+    };
+    let column = column.unwrap_or(1);
+    match name {
+        "code" | "code-insiders" | "cursor" | "windsurf" | "codium" => {
+            vec!["--goto".to_string(), format!("{path}:{line}:{column}")]
+        }
+        "subl" | "zed" | "hx" | "helix" => vec![format!("{path}:{line}:{column}")],
+        "nano" | "micro" => vec![format!("+{line},{column}"), path.to_string()],
+        "emacs" | "emacsclient" => vec![format!("+{line}:{column}"), path.to_string()],
+        "vi" | "vim" | "nvim" | "gvim" | "mvim" | "kak" => {
+            vec![format!("+{line}"), path.to_string()]
+        }
+        _ => vec![path.to_string()],
+    }
+}
+
+fn launch_command(
+    target: &OpenTarget,
+    path: &Path,
+    line: Option<u64>,
+    column: Option<u64>,
+) -> Command {
+    let path = path.to_string_lossy().to_string();
+    let mut args = target.base_args.clone();
+    match target.kind {
+        OpenTargetKind::VsCodeLike => {
+            args.push("--goto".to_string());
+            args.push(match line {
+                Some(line) => format!("{path}:{line}:{}", column.unwrap_or(1)),
+                None => path,
+            });
+        }
+        OpenTargetKind::JetBrains => {
+            if let Some(line) = line {
+                args.push("--line".to_string());
+                args.push(line.to_string());
+                if let Some(column) = column {
+                    args.push("--column".to_string());
+                    args.push(column.to_string());
+                }
+            }
+            args.push(path);
+        }
+        OpenTargetKind::EditorEnv | OpenTargetKind::SystemDefault => {
+            let name = editor_program_name(&target.program);
+            args.extend(editor_env_args(&name, &path, line, column));
+        }
+    }
+
+    let mut command = match target.terminal.as_ref() {
+        Some(terminal) => {
+            let mut command = Command::new(terminal);
+            command.arg("-e").arg(&target.program);
+            command
+        }
+        None => Command::new(&target.program),
+    };
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        // Launchers such as code.cmd would otherwise flash a console window.
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+pub(crate) fn open_file(
+    state: &AppState,
+    params: Option<&Map<String, Value>>,
+) -> Result<Value, String> {
+    let (path, line, column) = resolve_open_path(state, params)?;
+    let targets = open_targets(state)?;
+    let requested = param_str(params, &["target", "targetId", "appId", "app"])
+        .map(str::to_string)
+        .or(preferred_target(state)?);
+
+    let target = match requested {
+        Some(id) => targets
+            .iter()
+            .find(|target| target.id == id)
+            .ok_or_else(|| format!("Open target is not available: {id}"))?,
+        // Without a preference, prefer an editor that can jump to the line over the OS handler.
+        None => targets
+            .iter()
+            .find(|target| target.kind != OpenTargetKind::SystemDefault)
+            .or_else(|| targets.last())
+            .ok_or_else(|| "No open targets available".to_string())?,
+    };
+
+    if target.kind == OpenTargetKind::SystemDefault {
+        open::that(&path).map_err(|e| format!("open-file failed: {e}"))?;
+    } else {
+        launch_command(target, &path, line, column)
+            .spawn()
+            .map_err(|e| format!("Failed to launch {}: {e}", target.label))?;
+    }
+
+    Ok(json!({
+        "success": true,
+        "target": target.id,
+        "path": path.to_string_lossy()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_suffix_splits_line_and_column() {
+        assert_eq!(
+            split_location_suffix("missing/lib.rs:42:7"),
+            ("missing/lib.rs".to_string(), Some(42), Some(7))
+        );
+        assert_eq!(
+            split_location_suffix("missing/lib.rs:42"),
+            ("missing/lib.rs".to_string(), Some(42), None)
+        );
+        assert_eq!(
+            split_location_suffix("missing/lib.rs"),
+            ("missing/lib.rs".to_string(), None, None)
+        );
+    }
+
+    #[test]
+    fn location_suffix_keeps_non_numeric_and_extra_colons_in_the_path() {
+        assert_eq!(
+            split_location_suffix("missing/a:b.rs"),
+            ("missing/a:b.rs".to_string(), None, None)
+        );
+        assert_eq!(
+            split_location_suffix("missing/lib.rs:1:2:3"),
+            ("missing/lib.rs:1".to_string(), Some(2), Some(3))
+        );
+        assert_eq!(
+            split_location_suffix(r"C:\missing\lib.rs:9"),
+            (r"C:\missing\lib.rs".to_string(), Some(9), None)
+        );
+    }
+
+    #[test]
+    fn location_suffix_leaves_existing_paths_alone() {
+        let path = env!("CARGO_MANIFEST_DIR");
+        assert_eq!(split_location_suffix(path), (path.to_string(), None, None));
+    }
+
+    #[test]
+    fn editor_args_jump_to_line_and_column_per_editor() {
+        let args = |name| editor_env_args(name, "a.rs", Some(3), Some(5));
+        assert_eq!(args("code"), ["--goto", "a.rs:3:5"]);
+        assert_eq!(args("zed"), ["a.rs:3:5"]);
+        assert_eq!(args("nano"), ["+3,5", "a.rs"]);
+        assert_eq!(args("emacsclient"), ["+3:5", "a.rs"]);
+        assert_eq!(args("nvim"), ["+3", "a.rs"]);
+        assert_eq!(args("notepad"), ["a.rs"]);
+    }
+
+    #[test]
+    fn editor_args_default_the_column_and_skip_missing_lines() {
+        assert_eq!(
+            editor_env_args("cursor", "a.rs", Some(3), None),
+            ["--goto", "a.rs:3:1"]
+        );
+        assert_eq!(editor_env_args("vim", "a.rs", None, Some(5)), ["a.rs"]);
+    }
+
+    #[test]
+    fn editor_program_name_ignores_directory_and_extension() {
+        assert_eq!(editor_program_name(Path::new("/usr/bin/nvim")), "nvim");
+        assert_eq!(editor_program_name(Path::new("Code.EXE")), "code");
+    }
+}
@@ -0,0 +1,415 @@
+// This module snapshots the workspace's git tree when a turn starts and diffs it when it ends.
+// Trees are written through a scratch index, so the user's index, HEAD and stash stay untouched.
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::fs;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::git::{repository_root, run_git, run_git_with_env, split_unified_diff};
+use crate::{lock_or_err, AppState};
+
+const MAX_TURN_SNAPSHOTS: usize = 100;
+
+struct TurnSnapshot {
+    thread_id: String,
+    root: String,
+    before: String,
+    after: Option<String>,
+    reverted: bool,
+}
+
+// A `before` tree still being written while the turn's first request is already on its way.
+struct PendingSnapshot {
+    thread_id: String,
+    capture: JoinHandle<Option<(String, String)>>,
+}
+
+#[derive(Default)]
+struct SnapshotEntries {
+    pending: HashMap<String, PendingSnapshot>,
+    by_turn: HashMap<String, TurnSnapshot>,
+    order: VecDeque<String>,
+}
+
+#[derive(Default)]
+pub(crate) struct TurnSnapshotStore {
+    entries: Mutex<SnapshotEntries>,
+}
+
+fn scratch_path(kind: &str) -> PathBuf {
+    env::temp_dir().join(format!("codex-turn-{kind}-{}", Uuid::new_v4()))
+}
+
+// Seeding the scratch index from the real one lets `git add` reuse its stat cache.
+async fn write_worktree_tree(root: &Path) -> Result<String, String> {
+    let index = run_git(root, &["rev-parse", "--git-path", "index"]).await?;
+    let index = root.join(index.trim());
+    let scratch = scratch_path("index");
+    let _ = fs::copy(&index, &scratch).await;
+
+    let envs = [("GIT_INDEX_FILE", scratch.as_path())];
+    let tree = match run_git_with_env(root, &["add", "-A"], &envs).await {
+        Ok(_) => run_git_with_env(root, &["write-tree"], &envs).await,
+        Err(error) => Err(error),
+    };
+    let _ = fs::remove_file(&scratch).await;
+    let tree = tree?.trim().to_string();
+    if tree.is_empty() {
+        return Err("git write-tree returned no tree".to_string());
+    }
+    Ok(tree)
+}
+
+async fn capture_before_tree(turn_id: String, cwd: String) -> Option<(String, String)> {
+    let root = repository_root(&cwd).await?;
+    match write_worktree_tree(Path::new(&root)).await {
+        Ok(tree) => Some((root, tree)),
+        Err(error) => {
+            eprintln!("[tauri-host] turn snapshot for {turn_id} failed: {error}");
+            None
+        }
+    }
+}
+
+// Starts the `before` snapshot without waiting for it, so the turn is not held up by `git add`.
+pub(crate) fn begin_turn(state: &AppState, thread_id: &str, turn_id: &str, cwd: &str) {
+    let capture = tokio::spawn(capture_before_tree(turn_id.to_string(), cwd.to_string()));
+    match state.turn_snapshots.entries.lock() {
+        Ok(mut entries) => {
+            entries.pending.insert(
+                turn_id.to_string(),
+                PendingSnapshot {
+                    thread_id: thread_id.to_string(),
+                    capture,
+                },
+            );
+        }
+        Err(_) => capture.abort(),
+    }
+}
+
+// Waits for a turn's `before` snapshot and files it with the completed ones.
+async fn settle_turn(state: &AppState, turn_id: &str) {
+    let pending = match state.turn_snapshots.entries.lock() {
+        Ok(mut entries) => entries.pending.remove(turn_id),
+        Err(_) => return,
+    };
+    let Some(pending) = pending else {
+        return;
+    };
+    let Ok(Some((root, before))) = pending.capture.await else {
+        return;
+    };
+
+    let Ok(mut entries) = state.turn_snapshots.entries.lock() else {
+        return;
+    };
+    entries.by_turn.insert(
+        turn_id.to_string(),
+        TurnSnapshot {
+            thread_id: pending.thread_id,
+            root,
+            before,
+            after: None,
+            reverted: false,
+        },
+    );
+    entries.order.push_back(turn_id.to_string());
+    while entries.order.len() > MAX_TURN_SNAPSHOTS {
+        if let Some(evicted) = entries.order.pop_front() {
+            entries.by_turn.remove(&evicted);
+        }
+    }
+}
+
+pub(crate) fn discard_turn(state: &AppState, turn_id: &str) {
+    if let Ok(mut entries) = state.turn_snapshots.entries.lock() {
+        if let Some(pending) = entries.pending.remove(turn_id) {
+            pending.capture.abort();
+        }
+        entries.by_turn.remove(turn_id);
+        entries.order.retain(|id| id != turn_id);
+    }
+}
+
+pub(crate) fn forget_thread(state: &AppState, thread_id: &str) {
+    if let Ok(mut entries) = state.turn_snapshots.entries.lock() {
+        let SnapshotEntries {
+            pending,
+            by_turn,
+            order,
+        } = &mut *entries;
+        pending.retain(|_, snapshot| {
+            let keep = snapshot.thread_id != thread_id;
+            if !keep {
+                snapshot.capture.abort();
+            }
+            keep
+        });
+        by_turn.retain(|_, snapshot| snapshot.thread_id != thread_id);
+        order.retain(|id| by_turn.contains_key(id));
+    }
+}
+
+fn snapshot_trees(
+    state: &AppState,
+    turn_id: &str,
+) -> Result<Option<(String, String, Option<String>)>, String> {
+    let entries = lock_or_err(&state.turn_snapshots.entries, "turn_snapshots")?;
+    Ok(entries.by_turn.get(turn_id).map(|snapshot| {
+        (
+            snapshot.root.clone(),
+            snapshot.before.clone(),
+            snapshot.after.clone(),
+        )
+    }))
+}
+
+// Returns the turn's change summary: per-file line stats, totals and the unified patch.
+pub(crate) async fn finish_turn(state: &AppState, turn_id: &str) -> Option<Value> {
+    settle_turn(state, turn_id).await;
+    let (root, before, _) = snapshot_trees(state, turn_id).ok()??;
+    let root_path = Path::new(&root);
+    let after = match write_worktree_tree(root_path).await {
+        Ok(tree) => tree,
+        Err(error) => {
+            eprintln!("[tauri-host] turn snapshot for {turn_id} failed: {error}");
+            return None;
+        }
+    };
+    let patch = run_git(
+        root_path,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--binary",
+            &before,
+            &after,
+        ],
+    )
+    .await
+    .ok()?;
+
+    if let Ok(mut entries) = state.turn_snapshots.entries.lock() {
+        if let Some(snapshot) = entries.by_turn.get_mut(turn_id) {
+            snapshot.after = Some(after);
+        }
+    }
+
+    let files = split_unified_diff(&patch)
+        .into_iter()
+        .map(|file| {
+            json!({
+                "path": file["path"],
+                "additions": file["additions"],
+                "deletions": file["deletions"]
+            })
+        })
+        .collect::<Vec<_>>();
+    let total = |key: &str| {
+        files
+            .iter()
+            .filter_map(|file| file[key].as_u64())
+            .sum::<u64>()
+    };
+    Some(json!({
+        "root": root,
+        "files": files,
+        "additions": total("additions"),
+        "deletions": total("deletions"),
+        "patch": patch
+    }))
+}
+
+// Applies the inverse of the turn's patch; refuses when later edits touched the same lines.
+pub(crate) async fn revert_turn(state: &AppState, turn_id: &str) -> Result<Value, String> {
+    {
+        let entries = lock_or_err(&state.turn_snapshots.entries, "turn_snapshots")?;
+        if entries.pending.contains_key(turn_id) {
+            return Err(format!("Turn {turn_id} has not completed yet"));
+        }
+    }
+    let (root, before, after) = snapshot_trees(state, turn_id)?
+        .ok_or_else(|| format!("No snapshot recorded for turn {turn_id}"))?;
+    let after = after.ok_or_else(|| format!("Turn {turn_id} has not completed yet"))?;
+    {
+        let entries = lock_or_err(&state.turn_snapshots.entries, "turn_snapshots")?;
+        if entries.by_turn.get(turn_id).is_some_and(|s| s.reverted) {
+            return Err(format!("Turn {turn_id} was already reverted"));
+        }
+    }
+
+    let root_path = Path::new(&root);
+    let reverse = run_git(
+        root_path,
+        &[
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--binary",
+            &after,
+            &before,
+        ],
+    )
+    .await?;
+    let files = split_unified_diff(&reverse)
+        .into_iter()
+        .filter_map(|file| file["path"].as_str().map(str::to_string))
+        .collect::<Vec<_>>();
+
+    if !reverse.trim().is_empty() {
+        let patch_path = scratch_path("revert.patch");
+        fs::write(&patch_path, &reverse)
+            .await
+            .map_err(|e| format!("Failed to write revert patch: {e}"))?;
+        let patch_arg = patch_path.to_string_lossy().to_string();
+        let applied = match run_git(root_path, &["apply", "--check", &patch_arg]).await {
+            Ok(_) => run_git(root_path, &["apply", &patch_arg]).await,
+            Err(error) => Err(error),
+        };
+        let _ = fs::remove_file(&patch_path).await;
+        applied.map_err(|error| format!("Revert failed: {error}"))?;
+    }
+
+    let mut entries = lock_or_err(&state.turn_snapshots.entries, "turn_snapshots")?;
+    if let Some(snapshot) = entries.by_turn.get_mut(turn_id) {
+        snapshot.reverted = true;
+    }
+    Ok(json!({ "turnId": turn_id, "reverted": true, "files": files }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{create_backend, BackendMode};
+    use std::fs as std_fs;
+    use std::process::Command;
+
+    fn git(root: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .status()
+            .expect("git");
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn scratch_repo() -> PathBuf {
+        let root = scratch_path("repo");
+        std_fs::create_dir_all(&root).unwrap();
+        git(&root, &["init", "-q"]);
+        git(&root, &["config", "user.email", "test@example.com"]);
+        git(&root, &["config", "user.name", "Test"]);
+        std_fs::write(root.join("kept.txt"), "one\ntwo\n").unwrap();
+        std_fs::write(root.join("gone.txt"), "bye\n").unwrap();
+        git(&root, &["add", "-A"]);
+        git(&root, &["commit", "-q", "-m", "init"]);
+        root
+    }
+
+    // Records a finished turn that rewrote `kept.txt`, deleted `gone.txt` and added `new.txt`.
+    async fn finished_turn(state: &AppState, root: &Path) -> Value {
+        begin_turn(state, "thread-1", "turn-1", &root.to_string_lossy());
+        settle_turn(state, "turn-1").await;
+        std_fs::write(root.join("kept.txt"), "one\n2\n").unwrap();
+        std_fs::remove_file(root.join("gone.txt")).unwrap();
+        std_fs::write(root.join("new.txt"), "a\nb\n").unwrap();
+        finish_turn(state, "turn-1").await.expect("change summary")
+    }
+
+    #[test]
+    fn finish_turn_summarizes_the_turns_changes() {
+        let root = scratch_repo();
+        let state = AppState::with_backend(create_backend(BackendMode::Memory));
+        let summary = tauri::async_runtime::block_on(finished_turn(&state, &root));
+
+        let mut files = summary["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| {
+                (
+                    file["path"].as_str().unwrap_or_default().to_string(),
+                    file["additions"].as_u64().unwrap_or_default(),
+                    file["deletions"].as_u64().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                ("gone.txt".to_string(), 0, 1),
+                ("kept.txt".to_string(), 1, 1),
+                ("new.txt".to_string(), 2, 0)
+            ]
+        );
+        assert_eq!(summary["additions"], 3);
+        assert_eq!(summary["deletions"], 2);
+        assert!(summary["patch"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("+++ b/new.txt"));
+        // The user's index is left alone: nothing from the turn is staged.
+        let staged = Command::new("git")
+            .args(["diff", "--cached", "--name-only"])
+            .current_dir(&root)
+            .output()
+            .unwrap();
+        assert!(staged.stdout.is_empty());
+        let _ = std_fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn revert_turn_restores_the_worktree_once() {
+        let root = scratch_repo();
+        let state = AppState::with_backend(create_backend(BackendMode::Memory));
+        tauri::async_runtime::block_on(async {
+            finished_turn(&state, &root).await;
+
+            let reverted = revert_turn(&state, "turn-1").await.expect("revert");
+            assert_eq!(reverted["reverted"], true);
+            assert_eq!(reverted["files"].as_array().map(Vec::len), Some(3));
+            assert_eq!(
+                std_fs::read_to_string(root.join("kept.txt")).unwrap(),
+                "one\ntwo\n"
+            );
+            assert_eq!(
+                std_fs::read_to_string(root.join("gone.txt")).unwrap(),
+                "bye\n"
+            );
+            assert!(!root.join("new.txt").exists());
+
+            let again = revert_turn(&state, "turn-1").await.unwrap_err();
+            assert!(again.contains("already reverted"), "{again}");
+            let unknown = revert_turn(&state, "turn-2").await.unwrap_err();
+            assert!(unknown.contains("No snapshot"), "{unknown}");
+        });
+        let _ = std_fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn revert_turn_refuses_when_later_edits_overlap() {
+        let root = scratch_repo();
+        let state = AppState::with_backend(create_backend(BackendMode::Memory));
+        tauri::async_runtime::block_on(async {
+            finished_turn(&state, &root).await;
+            std_fs::write(root.join("kept.txt"), "one\nlater\n").unwrap();
+
+            let error = revert_turn(&state, "turn-1").await.unwrap_err();
+            assert!(error.starts_with("Revert failed"), "{error}");
+            assert_eq!(
+                std_fs::read_to_string(root.join("kept.txt")).unwrap(),
+                "one\nlater\n"
+            );
+            assert!(root.join("new.txt").exists());
+        });
+        let _ = std_fs::remove_dir_all(&root);
+    }
+}
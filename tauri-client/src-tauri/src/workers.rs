@@ -0,0 +1,385 @@
+// This module hosts the Rust side of the webview worker protocol.
+// Electron served these requests from worker threads; here each worker id maps to a handler.
+
+use futures_util::future::BoxFuture;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Window};
+use tokio::task::AbortHandle;
+
+use crate::{emit_worker_to_window, lock_or_err, message_type, AppState};
+
+pub(crate) type WorkerResult = Result<Value, String>;
+
+pub(crate) trait WorkerHandler: Send + Sync {
+    fn handle(&self, request: WorkerRequest) -> BoxFuture<'static, WorkerResult>;
+}
+
+// What a worker request needs from the app: its state and the view's worker channels.
+pub(crate) trait WorkerHost: Send + Sync {
+    fn state(&self) -> &AppState;
+    fn emit(&self, worker_id: &str, payload: Value) -> Result<(), String>;
+}
+
+struct WindowWorkerHost {
+    app: AppHandle,
+    window: Window,
+}
+
+impl WorkerHost for WindowWorkerHost {
+    fn state(&self) -> &AppState {
+        self.app.state::<AppState>().inner()
+    }
+
+    fn emit(&self, worker_id: &str, payload: Value) -> Result<(), String> {
+        emit_worker_to_window(&self.window, worker_id, payload)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct WorkerRequest {
+    host: Arc<dyn WorkerHost>,
+    pub(crate) worker_id: String,
+    pub(crate) id: Value,
+    pub(crate) method: String,
+    pub(crate) params: Value,
+}
+
+impl WorkerRequest {
+    pub(crate) fn state(&self) -> &AppState {
+        self.host.state()
+    }
+
+    // Streams a partial result on the worker channel; the final response still follows.
+    pub(crate) fn send_progress(&self, value: Value) {
+        let payload = worker_message(
+            &self.worker_id,
+            &self.id,
+            &self.method,
+            json!({ "type": "partial", "value": value }),
+        );
+        if let Err(error) = self.host.emit(&self.worker_id, payload) {
+            eprintln!("[tauri-worker] emit worker progress failed: {error}");
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct WorkerRegistry {
+    handlers: HashMap<String, Arc<dyn WorkerHandler>>,
+    in_flight: Mutex<HashMap<(String, String), AbortHandle>>,
+}
+
+impl WorkerRegistry {
+    pub(crate) fn register(&mut self, worker_id: &str, handler: Arc<dyn WorkerHandler>) {
+        self.handlers.insert(worker_id.to_string(), handler);
+    }
+
+    fn handler(&self, worker_id: &str) -> Option<Arc<dyn WorkerHandler>> {
+        self.handlers.get(worker_id).cloned()
+    }
+}
+
+fn request_key(worker_id: &str, id: &Value) -> (String, String) {
+    let id = match id {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    (worker_id.to_string(), id)
+}
+
+fn worker_message(worker_id: &str, id: &Value, method: &str, result: Value) -> Value {
+    json!({
+        "type": "worker-response",
+        "workerId": worker_id,
+        "response": {
+            "id": id,
+            "method": method,
+            "result": result
+        }
+    })
+}
+
+fn worker_response_payload(
+    worker_id: &str,
+    id: &Value,
+    method: &str,
+    result: WorkerResult,
+) -> Value {
+    let result = match result {
+        Ok(value) => json!({ "type": "ok", "value": value }),
+        Err(message) => json!({
+            "type": "error",
+            "error": { "message": message }
+        }),
+    };
+    worker_message(worker_id, id, method, result)
+}
+
+fn start_worker_request(
+    host: Arc<dyn WorkerHost>,
+    worker_id: &str,
+    message: &Value,
+) -> Result<(), String> {
+    let request = message.get("request").cloned().unwrap_or_else(|| json!({}));
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown")
+        .to_string();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let Some(handler) = host.state().workers.handler(worker_id) else {
+        return host.emit(
+            worker_id,
+            worker_response_payload(
+                worker_id,
+                &id,
+                &method,
+                Err(format!("No Tauri worker is registered for {worker_id}.")),
+            ),
+        );
+    };
+
+    let key = request_key(worker_id, &id);
+    let worker_request = WorkerRequest {
+        host: host.clone(),
+        worker_id: worker_id.to_string(),
+        id,
+        method,
+        params,
+    };
+
+    let mut in_flight = lock_or_err(&host.state().workers.in_flight, "worker_in_flight")?;
+    if let Some(previous) = in_flight.remove(&key) {
+        previous.abort();
+    }
+
+    let task_key = key.clone();
+    let task = tokio::spawn(async move {
+        let result = handler.handle(worker_request.clone()).await;
+        let host = &worker_request.host;
+        let still_active = {
+            let Ok(mut in_flight) = host.state().workers.in_flight.lock() else {
+                return;
+            };
+            in_flight.remove(&task_key).is_some()
+        };
+        if !still_active {
+            return;
+        }
+        let payload = worker_response_payload(
+            &worker_request.worker_id,
+            &worker_request.id,
+            &worker_request.method,
+            result,
+        );
+        if let Err(error) = host.emit(&worker_request.worker_id, payload) {
+            eprintln!("[tauri-worker] emit worker response failed: {error}");
+        }
+    });
+    in_flight.insert(key, task.abort_handle());
+    Ok(())
+}
+
+fn cancel_worker_request(
+    registry: &WorkerRegistry,
+    worker_id: &str,
+    message: &Value,
+) -> Result<(), String> {
+    let id = message
+        .get("requestId")
+        .or_else(|| message.get("id"))
+        .or_else(|| message.get("request").and_then(|request| request.get("id")))
+        .cloned()
+        .unwrap_or(Value::Null);
+    if id.is_null() {
+        return Ok(());
+    }
+
+    let mut in_flight = lock_or_err(&registry.in_flight, "worker_in_flight")?;
+    if let Some(handle) = in_flight.remove(&request_key(worker_id, &id)) {
+        handle.abort();
+    }
+    Ok(())
+}
+
+fn dispatch_worker_message(
+    host: Arc<dyn WorkerHost>,
+    worker_id: &str,
+    message: &Value,
+) -> Result<(), String> {
+    match message_type(message).unwrap_or_default() {
+        "worker-request" => start_worker_request(host, worker_id, message),
+        "worker-request-cancel" => cancel_worker_request(&host.state().workers, worker_id, message),
+        other => {
+            println!("[tauri-worker] unhandled worker message: {other}");
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn handle_worker_message(
+    app: &AppHandle,
+    window: &Window,
+    worker_id: &str,
+    message: &Value,
+) -> Result<(), String> {
+    let host = Arc::new(WindowWorkerHost {
+        app: app.clone(),
+        window: window.clone(),
+    });
+    dispatch_worker_message(host, worker_id, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{create_backend, BackendMode};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+    struct TestHost {
+        state: AppState,
+        sent: UnboundedSender<(String, Value)>,
+    }
+
+    impl WorkerHost for TestHost {
+        fn state(&self) -> &AppState {
+            &self.state
+        }
+
+        fn emit(&self, worker_id: &str, payload: Value) -> Result<(), String> {
+            self.sent
+                .send((worker_id.to_string(), payload))
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    // Reports progress, then echoes `value` after `delayMs`.
+    struct EchoWorker;
+
+    impl WorkerHandler for EchoWorker {
+        fn handle(&self, request: WorkerRequest) -> BoxFuture<'static, WorkerResult> {
+            Box::pin(async move {
+                request.send_progress(json!({ "started": request.params["value"] }));
+                let delay = request.params["delayMs"].as_u64().unwrap_or_default();
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Ok(request.params["value"].clone())
+            })
+        }
+    }
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    // Reports progress and never finishes; the flag is set once its future is dropped.
+    struct StuckWorker(Arc<AtomicBool>);
+
+    impl WorkerHandler for StuckWorker {
+        fn handle(&self, request: WorkerRequest) -> BoxFuture<'static, WorkerResult> {
+            let dropped = SetOnDrop(self.0.clone());
+            Box::pin(async move {
+                let _dropped = dropped;
+                request.send_progress(json!("started"));
+                std::future::pending::<()>().await;
+                Ok(Value::Null)
+            })
+        }
+    }
+
+    fn test_host(
+        handler: Arc<dyn WorkerHandler>,
+    ) -> (Arc<TestHost>, UnboundedReceiver<(String, Value)>) {
+        let mut state = AppState::with_backend(create_backend(BackendMode::Memory));
+        state.workers.register("test", handler);
+        let (sent, received) = unbounded_channel();
+        (Arc::new(TestHost { state, sent }), received)
+    }
+
+    fn worker_request(id: Value, params: Value) -> Value {
+        json!({
+            "type": "worker-request",
+            "request": { "id": id, "method": "echo", "params": params }
+        })
+    }
+
+    #[test]
+    fn responses_and_progress_are_correlated_with_their_request() {
+        tauri::async_runtime::block_on(async {
+            let (host, mut sent) = test_host(Arc::new(EchoWorker));
+            let slow = worker_request(json!(1), json!({ "value": "slow", "delayMs": 50 }));
+            let fast = worker_request(json!("two"), json!({ "value": "fast" }));
+            dispatch_worker_message(host.clone(), "test", &slow).unwrap();
+            dispatch_worker_message(host.clone(), "test", &fast).unwrap();
+
+            let expected = |id: &Value| if *id == json!(1) { "slow" } else { "fast" };
+            let (mut partials, mut finals) = (0, Vec::new());
+            while finals.len() < 2 {
+                let (worker_id, payload) = sent.recv().await.unwrap();
+                assert_eq!(worker_id, "test");
+                assert_eq!(payload["type"], "worker-response");
+                let response = &payload["response"];
+                assert_eq!(response["method"], "echo");
+                let result = &response["result"];
+                match result["type"].as_str() {
+                    Some("partial") => {
+                        assert_eq!(result["value"]["started"], expected(&response["id"]));
+                        partials += 1;
+                    }
+                    Some("ok") => {
+                        assert_eq!(result["value"], expected(&response["id"]));
+                        finals.push(response["id"].clone());
+                    }
+                    other => panic!("unexpected result type {other:?}"),
+                }
+            }
+            assert_eq!(partials, 2);
+            assert_eq!(finals, vec![json!("two"), json!(1)]);
+            assert!(host.state.workers.in_flight.lock().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn cancel_aborts_the_in_flight_task_without_a_response() {
+        tauri::async_runtime::block_on(async {
+            let dropped = Arc::new(AtomicBool::new(false));
+            let (host, mut sent) = test_host(Arc::new(StuckWorker(dropped.clone())));
+            let request = worker_request(json!(7), json!({}));
+            dispatch_worker_message(host.clone(), "test", &request).unwrap();
+            let (_, progress) = sent.recv().await.unwrap();
+            assert_eq!(progress["response"]["result"]["type"], "partial");
+
+            let cancel = json!({ "type": "worker-request-cancel", "requestId": 7 });
+            dispatch_worker_message(host.clone(), "test", &cancel).unwrap();
+            for _ in 0..100 {
+                if dropped.load(Ordering::SeqCst) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            assert!(dropped.load(Ordering::SeqCst));
+            assert!(host.state.workers.in_flight.lock().unwrap().is_empty());
+            assert!(sent.try_recv().is_err());
+        });
+    }
+
+    #[test]
+    fn unregistered_workers_answer_with_an_error() {
+        let (host, mut sent) = test_host(Arc::new(EchoWorker));
+        let request = worker_request(json!(3), json!({}));
+        dispatch_worker_message(host, "missing", &request).unwrap();
+        let (worker_id, payload) = sent.try_recv().unwrap();
+        assert_eq!(worker_id, "missing");
+        assert_eq!(payload["response"]["id"], 3);
+        assert_eq!(payload["response"]["result"]["type"], "error");
+    }
+}